@@ -1,3 +1,4 @@
+use bili_live_chat::api::live::LiveRoom;
 use bili_live_chat::config::Config;
 use bili_live_chat::App;
 use bili_live_chat::Credential;
@@ -10,9 +11,12 @@ async fn main() {
             bili_jct: "your bili_jct".to_owned(),
             buvid3: "your buvid3".to_owned(),
         },
+        ..Default::default()
     };
 
     // 3044248 魔法Zc目录 直播间
-    let mut app = App::new(3044248, conf).await;
+    let mut app = App::new(vec![LiveRoom::room_init(3044248).await.unwrap()], conf)
+        .await
+        .unwrap();
     app.run().await;
 }
@@ -1,10 +1,10 @@
-use bili_live_chat::{client::DanmakuClient, Message};
+use bili_live_chat::{client::DanmakuClient, config::Config, LiveEvent};
 use tokio::sync::mpsc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (tx, _) = mpsc::channel::<Message>(512);
-    let mut app = DanmakuClient::new(3, tx);
+    let (tx, _) = mpsc::channel::<LiveEvent>(512);
+    let mut app = DanmakuClient::new(3, &Config::default(), tx);
     let _ = app.connect().await?;
     Ok(())
 }
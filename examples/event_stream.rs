@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use bili_live_chat::{client::EventStream, config::Config, LiveEvent};
+use futures::StreamExt;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 3044248 魔法Zc目录 直播间
+    let (mut stream, sender) = EventStream::connect(3044248, &Config::default()).await?;
+
+    // keep the connection alive
+    let heart_beat = sender.clone();
+    tokio::spawn(async move {
+        while heart_beat.heart_beat().await.is_ok() {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        }
+    });
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            LiveEvent::Danmaku(danmaku) => println!("{:?}", danmaku),
+            LiveEvent::Popularity(popularity) => println!("popularity: {}", popularity),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -2,18 +2,18 @@
 
 use std::{io, time::SystemTime};
 
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Utc};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, ModifierKeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use tui::{
     backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Color, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs, Widget},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
     Frame, Terminal,
 };
 
@@ -147,7 +147,7 @@ fn ui<B: Backend>(f: &mut Frame<B>, aw: &mut AppWidgets) {
         .margin(0)
         .constraints([Constraint::Length(1), Constraint::Min(3)].as_ref())
         .split(f.size());
-    let tabs_title = vec!["tab1".to_owned(), "tab2".to_owned(), "tab3".to_owned()];
+    let tabs_title = ["tab1".to_owned(), "tab2".to_owned(), "tab3".to_owned()];
     let title = tabs_title
         .iter()
         .map(|item| Spans::from(Span::from(item.as_str())))
@@ -171,8 +171,7 @@ fn draw_first_tab<B: Backend>(f: &mut Frame<B>, aw: &mut AppWidgets, area: Rect)
     let list: ChatHistory = aw
         .chat_history
         .iter()
-        .enumerate()
-        .map(|(_, m)| {
+        .map(|m| {
             let dt = DateTime::<Utc>::from(SystemTime::now());
             let title = Spans::from(format!("{}", dt));
             let content = Spans::from(Span::raw(m));
@@ -207,12 +206,12 @@ fn draw_first_tab<B: Backend>(f: &mut Frame<B>, aw: &mut AppWidgets, area: Rect)
     }
 }
 
-fn draw_second_tab<B: Backend>(f: &mut Frame<B>, aw: &mut AppWidgets, area: Rect) {
+fn draw_second_tab<B: Backend>(f: &mut Frame<B>, _aw: &mut AppWidgets, area: Rect) {
     let block = Block::default().title("Tab 2").borders(Borders::ALL);
     f.render_widget(block, area);
 }
 
-fn draw_third_tab<B: Backend>(f: &mut Frame<B>, aw: &mut AppWidgets, area: Rect) {
+fn draw_third_tab<B: Backend>(f: &mut Frame<B>, _aw: &mut AppWidgets, area: Rect) {
     let block = Block::default().title("Tab 3").borders(Borders::ALL);
     f.render_widget(block, area);
 }
@@ -0,0 +1,18 @@
+#![no_main]
+
+use bili_live_chat::client::Decoder;
+use libfuzzer_sys::fuzz_target;
+
+// Feed the data in two pieces split by its first byte, the decoder must never panic.
+fuzz_target!(|data: &[u8]| {
+    let Some((&split, data)) = data.split_first() else {
+        return;
+    };
+    let (head, tail) = data.split_at((split as usize).min(data.len()));
+
+    let mut decoder = Decoder::new();
+    for piece in [head, tail] {
+        let _ = decoder.feed(piece);
+        while decoder.next_frame().is_some() {}
+    }
+});
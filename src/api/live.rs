@@ -1,8 +1,59 @@
 use crate::request::Request;
-use crate::Credential;
+use crate::{Credential, Error, OnlineRankEntry, Result};
 use std::collections::HashMap;
+use std::str::FromStr;
 
-#[derive(Debug)]
+// The ids of a live room, resolved by room_init.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoomIds {
+    pub room_id: u32,  /* the real room id, which the websocket requires */
+    pub short_id: u32, /* the short (vanity) id, 0 if the room has none */
+    pub uid: u64,      /* uid of the streamer */
+}
+
+// What the user gives to specify a live room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomInput {
+    // A short or real room id, e.g. "510" or "https://live.bilibili.com/510?spm_id_from=..."
+    RoomId(u32),
+    // The uid of the streamer, e.g. "uid:50329118" or "https://space.bilibili.com/50329118"
+    Uid(u64),
+}
+
+impl FromStr for RoomInput {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidInput(format!("live room '{}'", s));
+        let input = s.trim();
+        let input = input
+            .strip_prefix("https://")
+            .or_else(|| input.strip_prefix("http://"))
+            .unwrap_or(input);
+        let input = input.strip_prefix("www.").unwrap_or(input);
+        // drop the query and the fragment of an url
+        let input = input.split(['?', '#']).next().unwrap_or_default();
+
+        if let Some(uid) = input.strip_prefix("uid:") {
+            uid.trim().parse().map(Self::Uid).map_err(|_| invalid())
+        } else if let Some(path) = input.strip_prefix("space.bilibili.com/") {
+            let uid = path.split('/').next().unwrap_or_default();
+            uid.parse().map(Self::Uid).map_err(|_| invalid())
+        } else if let Some(path) = input.strip_prefix("live.bilibili.com/") {
+            // e.g. "live.bilibili.com/510", "live.bilibili.com/h5/510"
+            let room_id = path.split('/').rfind(|v| !v.is_empty());
+            room_id
+                .unwrap_or_default()
+                .parse()
+                .map(Self::RoomId)
+                .map_err(|_| invalid())
+        } else {
+            input.parse().map(Self::RoomId).map_err(|_| invalid())
+        }
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct LiveRoom {
     room_display_id: i64,
     credential: Credential,
@@ -16,7 +67,7 @@ impl LiveRoom {
         }
     }
 
-    pub async fn send_normal_danmaku(&self, danmaku_text: &str) {
+    pub async fn send_normal_danmaku(&self, danmaku_text: &str) -> Result<()> {
         let mut danmaku = HashMap::new();
         let timestamp = (chrono::Utc::now() + chrono::Duration::hours(8)).timestamp();
 
@@ -28,7 +79,7 @@ impl LiveRoom {
         danmaku.insert("rnd".to_owned(), timestamp.to_string());
         danmaku.insert("bubble".to_owned(), 0.to_string());
 
-        if let Err(e) = Request::send(
+        Request::send_json(
             "POST",
             "https://api.live.bilibili.com/msg/send",
             None,
@@ -36,19 +87,79 @@ impl LiveRoom {
             Some(&self.credential),
             false,
         )
-        .await
-        {
-            eprintln!("[Error] send_normal_danmaku: {:#?}", e);
+        .await?;
+
+        Ok(())
+    }
+
+    // Look up the real room id, the short id and the streamer of a short or real room id.
+    pub async fn room_init(room_id: u32) -> Result<RoomIds> {
+        let mut params = HashMap::new();
+        params.insert("id".to_owned(), room_id.to_string());
+        let value = Request::send_json(
+            "GET",
+            "https://api.live.bilibili.com/room/v1/Room/room_init",
+            Some(&params),
+            None,
+            None,
+            true,
+        )
+        .await?;
+
+        let real_room_id = match value["data"]["room_id"].as_u64().unwrap_or_default() {
+            0 => {
+                return Err(Error::Decode(format!(
+                    "no 'room_id' in the room_init response of room {}",
+                    room_id
+                )))
+            }
+            real_room_id => real_room_id as u32,
+        };
+
+        Ok(RoomIds {
+            room_id: real_room_id,
+            short_id: value["data"]["short_id"].as_u64().unwrap_or_default() as u32,
+            uid: value["data"]["uid"].as_u64().unwrap_or_default(),
+        })
+    }
+
+    // Look up the live room of a streamer.
+    pub async fn room_id_by_uid(uid: u64) -> Result<u32> {
+        let mut params = HashMap::new();
+        params.insert("mid".to_owned(), uid.to_string());
+        let value = Request::send_json(
+            "GET",
+            "https://api.live.bilibili.com/room/v1/Room/getRoomInfoOld",
+            Some(&params),
+            None,
+            None,
+            true,
+        )
+        .await?;
+
+        match value["data"]["roomid"].as_u64().unwrap_or_default() {
+            0 => Err(Error::InvalidInput(format!("uid {} has no live room", uid))),
+            room_id => Ok(room_id as u32),
         }
     }
 
-    pub async fn get_rank_info(room_id: i64, ruid: i64, page: i32) -> Option<Vec<String>> {
+    // Resolve what the user gives to the ids of the live room.
+    pub async fn resolve(input: RoomInput) -> Result<RoomIds> {
+        let room_id = match input {
+            RoomInput::RoomId(room_id) => room_id,
+            RoomInput::Uid(uid) => Self::room_id_by_uid(uid).await?,
+        };
+
+        Self::room_init(room_id).await
+    }
+
+    pub async fn get_rank_info(room_id: i64, ruid: i64, page: i32) -> Result<Vec<OnlineRankEntry>> {
         let mut params = HashMap::new();
         params.insert("roomId".to_owned(), room_id.to_string());
         params.insert("page".to_owned(), page.to_string());
         params.insert("ruid".to_owned(), ruid.to_string());
         params.insert("pageSize".to_owned(), 50.to_string());
-        let resp = Request::send(
+        let value = Request::send_json(
             "GET",
             "https://api.live.bilibili.com/xlive/general-interface/v1/rank/getOnlineGoldRank",
             Some(&params),
@@ -56,51 +167,32 @@ impl LiveRoom {
             None,
             true,
         )
-        .await;
-
-        match resp {
-            Ok(data) => {
-                let value: serde_json::Value =
-                    serde_json::from_str(data.text().await.unwrap().as_str()).unwrap();
-                // format: name,guard_level,score
-                let mut out: Vec<String> = vec![];
-                if value["data"]["OnlineRankItem"].is_array() {
-                    value["data"]["OnlineRankItem"]
-                        .as_array()
-                        .unwrap()
-                        .iter()
-                        .map(|v| {
-                            // format: name,guard_level,score
-                            out.push(
-                                v["name"]
-                                    .as_str()
-                                    .unwrap()
-                                    .trim_end_matches("\"")
-                                    .trim_start_matches("\"")
-                                    .to_string()
-                                    + ","
-                                    + v["guard_level"].to_string().as_str()
-                                    + ","
-                                    + v["score"].to_string().as_str(),
-                            );
-                        })
-                        .count(); // The iterator is lazy, so it must be used.
-                }
+        .await?;
 
-                Some(out)
+        let mut out: Vec<OnlineRankEntry> = vec![];
+        if let Some(items) = value["data"]["OnlineRankItem"].as_array() {
+            for v in items {
+                out.push(OnlineRankEntry {
+                    uid: v["uid"].as_u64().unwrap_or_default(),
+                    uname: v["name"].as_str().unwrap_or_default().to_owned(),
+                    score: v["score"].as_u64().unwrap_or_default(),
+                    rank: v["userRank"].as_u64().unwrap_or_default() as u32,
+                    guard_level: v["guard_level"].as_u64().unwrap_or_default() as u8,
+                });
             }
-            Err(_) => None,
         }
+
+        Ok(out)
     }
 
-    pub async fn get_rank_info_first_50(room_id: i64, ruid: i64) -> Option<Vec<String>> {
+    pub async fn get_rank_info_first_50(room_id: i64, ruid: i64) -> Result<Vec<OnlineRankEntry>> {
         Self::get_rank_info(room_id, ruid, 1).await
     }
 
-    pub async fn get_room_info(room_display_id: i64) -> Option<HashMap<String, String>> {
+    pub async fn get_room_info(room_display_id: i64) -> Result<HashMap<String, String>> {
         let mut params = HashMap::new();
         params.insert("room_id".to_owned(), room_display_id.to_string());
-        let resp = Request::send(
+        let value = Request::send_json(
             "GET",
             "https://api.live.bilibili.com/xlive/web-room/v1/index/getInfoByRoom",
             Some(&params),
@@ -108,126 +200,127 @@ impl LiveRoom {
             None,
             true,
         )
-        .await;
-
-        match resp {
-            Ok(data) => {
-                let value: serde_json::Value =
-                    serde_json::from_str(data.text().await.unwrap().as_str()).unwrap();
-                let mut out = HashMap::new();
-                out.insert(
-                    "ruid".to_owned(),
-                    value["data"]["room_info"]["uid"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "room_id".to_owned(),
-                    value["data"]["room_info"]["room_id"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "title".to_owned(),
-                    value["data"]["room_info"]["title"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "tags".to_owned(),
-                    value["data"]["room_info"]["tags"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "description".to_owned(),
-                    value["data"]["room_info"]["description"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "area_name".to_owned(),
-                    value["data"]["room_info"]["area_name"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "parent_area_name".to_owned(),
-                    value["data"]["room_info"]["parent_area_name"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "live_start_time".to_owned(),
-                    value["data"]["room_info"]["live_start_time"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "watched_show".to_owned(),
-                    value["data"]["watched_show"]["num"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "attention".to_owned(),
-                    value["data"]["anchor_info"]["relation_info"]["attention"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "uname".to_owned(),
-                    value["data"]["anchor_info"]["base_info"]["uname"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert(
-                    "total_likes".to_owned(),
-                    value["data"]["like_info_v3"]["total_likes"]
-                        .to_string()
-                        .trim_start_matches("\"")
-                        .trim_end_matches("\"")
-                        .to_string(),
-                );
-                out.insert("rank_info".to_owned(), {
-                    if let Some(rank_info) =
-                        Self::parse_rank_info(&value["data"]["online_gold_rank_info_v2"]["list"])
-                    {
-                        rank_info
-                            .trim_start_matches("\"")
-                            .trim_end_matches("\"")
-                            .to_string()
-                    } else {
-                        "".to_owned()
-                    }
-                });
+        .await?;
 
-                Some(out)
+        let mut out = HashMap::new();
+        out.insert(
+            "ruid".to_owned(),
+            value["data"]["room_info"]["uid"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "room_id".to_owned(),
+            value["data"]["room_info"]["room_id"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "title".to_owned(),
+            value["data"]["room_info"]["title"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "tags".to_owned(),
+            value["data"]["room_info"]["tags"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "description".to_owned(),
+            value["data"]["room_info"]["description"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "area_name".to_owned(),
+            value["data"]["room_info"]["area_name"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "parent_area_name".to_owned(),
+            value["data"]["room_info"]["parent_area_name"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "live_status".to_owned(),
+            value["data"]["room_info"]["live_status"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "live_start_time".to_owned(),
+            value["data"]["room_info"]["live_start_time"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "watched_show".to_owned(),
+            value["data"]["watched_show"]["num"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "attention".to_owned(),
+            value["data"]["anchor_info"]["relation_info"]["attention"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "uname".to_owned(),
+            value["data"]["anchor_info"]["base_info"]["uname"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert(
+            "total_likes".to_owned(),
+            value["data"]["like_info_v3"]["total_likes"]
+                .to_string()
+                .trim_start_matches("\"")
+                .trim_end_matches("\"")
+                .to_string(),
+        );
+        out.insert("rank_info".to_owned(), {
+            if let Some(rank_info) =
+                Self::parse_rank_info(&value["data"]["online_gold_rank_info_v2"]["list"])
+            {
+                rank_info
+                    .trim_start_matches("\"")
+                    .trim_end_matches("\"")
+                    .to_string()
+            } else {
+                "".to_owned()
             }
-            Err(_) => None,
-        }
+        });
+
+        Ok(out)
     }
 
     fn parse_rank_info(list: &serde_json::Value) -> Option<String> {
@@ -246,3 +339,38 @@ impl LiveRoom {
         }
     }
 }
+
+#[test]
+fn test_room_input_from_str() {
+    let cases = [
+        ("510", RoomInput::RoomId(510)),
+        (" 3044248 ", RoomInput::RoomId(3044248)),
+        ("https://live.bilibili.com/510", RoomInput::RoomId(510)),
+        (
+            "https://live.bilibili.com/510?spm_id_from=333.1007&live_from=85001",
+            RoomInput::RoomId(510),
+        ),
+        (
+            "live.bilibili.com/h5/21452505/",
+            RoomInput::RoomId(21452505),
+        ),
+        ("uid:50329118", RoomInput::Uid(50329118)),
+        (
+            "https://space.bilibili.com/50329118/dynamic",
+            RoomInput::Uid(50329118),
+        ),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(input.parse::<RoomInput>().unwrap(), expected, "{}", input);
+    }
+
+    for input in [
+        "",
+        "abc",
+        "uid:",
+        "https://live.bilibili.com/",
+        "https://example.com/510",
+    ] {
+        assert!(input.parse::<RoomInput>().is_err(), "{}", input);
+    }
+}
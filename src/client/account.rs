@@ -2,6 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::request::Request;
+use crate::{Credential, Error, Result};
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Account {
     #[serde(rename = "mid")]
@@ -21,3 +24,44 @@ pub struct Account {
     #[serde(rename = "rank")]
     pub rank: String,
 }
+
+impl Account {
+    // Resolve the account logged in with the credential.
+    // If the credential is invalid or expired, return Ok(None).
+    pub async fn from_credential(credential: &Credential) -> Result<Option<Self>> {
+        if credential.session_data.is_empty() {
+            return Ok(None);
+        }
+
+        let value = match Request::send_json(
+            "GET",
+            "https://api.bilibili.com/x/web-interface/nav",
+            None,
+            None,
+            Some(credential),
+            true,
+        )
+        .await
+        {
+            Ok(value) => value,
+            // -101: not logged in
+            Err(Error::Api { code: -101, .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !value["data"]["isLogin"].as_bool().unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let mid = value["data"]["mid"]
+            .as_i64()
+            .ok_or_else(|| Error::Decode("no 'mid' in the nav response".to_owned()))?;
+        Ok(Some(Self {
+            mid,
+            uname: value["data"]["uname"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned(),
+            ..Default::default()
+        }))
+    }
+}
@@ -1,44 +1,62 @@
 #![allow(dead_code)]
 
-use crate::{Message, MessageKind};
+use crate::{LiveEvent, RawCommand, Reconnecting, MALFORMED_PACK_CMD};
 use futures::{
     stream::{SplitSink, SplitStream},
     SinkExt, StreamExt,
 };
-use serde::{Deserialize, Serialize};
-use tokio::{net::TcpStream, sync::mpsc::Sender};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, fs, io::Write, sync::Arc, time::Duration};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc::Sender, Mutex},
+};
 use tokio_tungstenite as tungstenite;
 use tungstenite::{tungstenite::protocol::Message as WssMessage, MaybeTlsStream};
 
-use crate::client::Account;
+use crate::client::{Account, Decoder, Frame, Operation, Packet, PROTO_VER_INT};
+use crate::config::Config;
+use crate::request::Request;
 use crate::utils;
+use crate::{Credential, Error, Result};
 
 type WebSocketStream = tungstenite::WebSocketStream<MaybeTlsStream<TcpStream>>;
+pub type WebSocketWriter = Arc<Mutex<SplitSink<WebSocketStream, WssMessage>>>;
+
+// How long to wait for the authentication response pack after sending the authentication pack.
+pub const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub trait Pack {
     fn into_bytes(self) -> Vec<u8>;
 
-    fn from_bytes<'de>(data: Vec<u8>) -> Self
+    fn from_bytes(data: &[u8]) -> Result<Self>
     where
-        Self: Sized + Deserialize<'de>,
+        Self: Sized + DeserializeOwned,
     {
-        let de_json_raw: serde_json::Value = serde_json::from_slice(data.as_slice()).unwrap();
-        Self::deserialize(de_json_raw).unwrap()
+        Ok(serde_json::from_slice(data)?)
     }
 }
 
 #[derive(Debug, Default)]
 pub struct DanmakuClient {
-    client: reqwest::Client,    /* Http Client */
-    account: Account,           /* BiliBili Account */
-    room_id: u32,               /* Room ID */
-    token: String,              /* Token */
-    host_list: Vec<HostServer>, /* Danmu Host Server List */
-    host_index: u8,             /* Index of Danmu Host Server Connected */
-    // When the function connect() finishes, conn_write will be taken and returned to outside.
-    conn_write: Option<SplitSink<WebSocketStream, WssMessage>>, /* Connection with Danmu Host Server */
+    client: reqwest::Client,         /* Http Client */
+    account: Account,                /* BiliBili Account */
+    credential: Credential,          /* Credential Of The BiliBili Account */
+    room_id: u32,                    /* Room ID */
+    token: String,                   /* Token */
+    host_list: Vec<HostServer>,      /* Danmu Host Server List */
+    host_index: u8,                  /* Index of Danmu Host Server Connected */
+    proto_ver: u8,                   /* Protocol Version Sent In Auth Pack */
+    api_base: String,                /* Base Url Of The Live Api */
+    auth_result: Option<Result<()>>, /* Result Of The Last Auth Reply */
+    popularity: u32,                 /* Popularity From The Last Heart Beat Reply */
+    decoder: Decoder,                /* Decoder Of The Packs Received */
+    // When the function connect() finishes, a clone of conn_write will be returned to outside.
+    // The writer inside is replaced on reconnecting, so the clone outside keeps working.
+    conn_write: Option<WebSocketWriter>, /* Connection with Danmu Host Server */
     conn_read: Option<SplitStream<WebSocketStream>>, /* Connection with Danmu Host Server */
-    mpsc_tx: Option<Sender<Message>>,                /* Channel Sender */
+    mpsc_tx: Option<Sender<LiveEvent>>,  /* Channel Sender */
+    dump_file: Option<fs::File>,         /* File Unknown Commands Are Appended To */
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,19 +67,10 @@ struct HostServer {
     wss_port: u32,
 }
 
-#[derive(Debug)]
-pub enum DataPack {
-    Auth,          /* Authentication Pack */
-    AuthResp,      /* Authentication Response Pack */
-    HeartBeat,     /* Heart Beat Pack */
-    HeartBeatResp, /* Heart Beat Response Pack */
-    Normal,        /* Normal Pack */
-}
-
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct AuthPack {
     #[serde(rename = "uid")]
-    _uid: u32,
+    _uid: u64,
     #[serde(rename = "roomid")]
     _room_id: u32,
     #[serde(rename = "protover")]
@@ -72,232 +81,319 @@ pub struct AuthPack {
     _type: u32,
     #[serde(rename = "key")]
     _key: String,
+    #[serde(rename = "buvid", skip_serializing_if = "String::is_empty", default)]
+    _buvid: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default)]
-pub struct AuthRespPack {}
+pub struct AuthRespPack {
+    #[serde(rename = "code")]
+    pub code: i64,
+}
 
 impl DanmakuClient {
-    pub fn new(room_id: u32, mpsc_tx: Sender<Message>) -> Self {
+    pub fn new(room_id: u32, config: &Config, mpsc_tx: Sender<LiveEvent>) -> Self {
+        // dumping is only for debugging, so the client keeps working without the file
+        let dump_file = config.dump_unknown.as_ref().and_then(|path| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .ok()
+        });
+
         Self {
             room_id,
+            proto_ver: config.proto_ver,
+            api_base: config.api_base().to_owned(),
+            credential: config.credential.clone(),
             mpsc_tx: Some(mpsc_tx),
+            dump_file,
             ..Default::default()
         }
     }
 
-    async fn init_client(&mut self) -> Result<(), reqwest::Error> {
-        // send request to get token
-        let resp = self
-            .client
-            .get(format!(
-                "https://api.live.bilibili.com/xlive/web-room/v1/index/getDanmuInfo?id={}&type=0",
-                self.room_id
-            ))
-            .send()
-            .await?;
-        // convert to 'serde_json::Value' instance
-        let json: serde_json::Value = serde_json::from_str(&resp.text().await?).unwrap();
-        let token = json["data"]["token"].as_str().unwrap();
-        self.token = token.to_owned();
-        // extract the danmu host server list and append into 'self.host_list'
-        let host_list_raw = json["data"]["host_list"].as_array().unwrap();
-        for obj in host_list_raw {
-            self.host_list.push(HostServer::deserialize(obj).unwrap());
+    async fn init_client(&mut self) -> Result<()> {
+        // resolve the logged-in account, then the server will not mask usernames
+        // if it fails, connect as an anonymous viewer and resolve it again on reconnecting
+        if self.account.mid == 0 {
+            if let Ok(Some(account)) = Account::from_credential(&self.credential).await {
+                self.account = account;
+            }
+        }
+
+        // send request (with cookies) to get token
+        let mut params = HashMap::new();
+        params.insert("id".to_owned(), self.room_id.to_string());
+        params.insert("type".to_owned(), 0.to_string());
+        let credential = if self.account.mid != 0 {
+            Some(&self.credential)
+        } else {
+            None
+        };
+        let json = Request::send_json(
+            "GET",
+            &format!("{}/xlive/web-room/v1/index/getDanmuInfo", self.api_base),
+            Some(&params),
+            None,
+            credential,
+            true,
+        )
+        .await?;
+        if let Some(token) = json["data"]["token"].as_str() {
+            self.token = token.to_owned();
+        }
+        // extract the danmu host server list and replace 'self.host_list'
+        if let Some(host_list_raw) = json["data"]["host_list"].as_array() {
+            self.host_list = host_list_raw
+                .iter()
+                .filter_map(|obj| HostServer::deserialize(obj).ok())
+                .collect();
         }
 
         Ok(())
     }
 
-    async fn shake_hands(&mut self) {
-        for (i, j) in self.host_list.iter().enumerate() {
-            match tungstenite::connect_async(format!("wss://{}:{}/sub", j.host, j.wss_port)).await {
-                Ok(conn_raw) => {
-                    let (write, read) = conn_raw.0.split();
-                    self.conn_write = Some(write);
-                    self.conn_read = Some(read);
-                    self.host_index = i as u8;
-                    break;
-                }
-                Err(e) => eprintln!("{:#?}", e),
+    // Shake hands with the danmu host server at 'index' of the host list.
+    async fn shake_hands(&mut self, index: usize) -> Result<()> {
+        let j = &self.host_list[index];
+        // a host without a wss port (e.g. a local mock server) is connected by plain ws
+        let url = match j.wss_port {
+            0 => format!("ws://{}:{}/sub", j.host, j.ws_port),
+            wss_port => format!("wss://{}:{}/sub", j.host, wss_port),
+        };
+        let (conn_raw, _) = tungstenite::connect_async(url).await?;
+        let (write, read) = conn_raw.split();
+        match self.conn_write {
+            Some(ref conn_write) => *conn_write.lock().await = write,
+            None => self.conn_write = Some(Arc::new(Mutex::new(write))),
+        }
+        self.conn_read = Some(read);
+        // the bytes left by the last connection are never completed
+        self.decoder = Decoder::new();
+        self.host_index = index as u8;
+
+        Ok(())
+    }
+
+    // Try the danmu host servers one by one, starting from the server at 'start',
+    // until one of them is connected and accepts the authentication pack.
+    async fn connect_hosts(&mut self, start: usize) -> Result<()> {
+        let len = self.host_list.len();
+        let mut last_err = Error::NoHostAvailable;
+        for i in (start..start + len).map(|i| i % len) {
+            if let Err(e) = self.shake_hands(i).await {
+                last_err = e;
+                continue;
+            }
+
+            if let Err(e) = self.send_auth().await {
+                last_err = e;
+                continue;
+            }
+            match self.wait_auth_resp().await {
+                Ok(()) => return Ok(()),
+                // The token is rejected, and other servers will reject it too.
+                Err(e @ Error::AuthRejected(_)) => return Err(e),
+                // Nobody receives the live events any more.
+                Err(Error::ChannelClosed) => return Err(Error::ChannelClosed),
+                Err(e) => last_err = e,
             }
         }
+
+        Err(last_err)
     }
 
-    async fn send(&mut self, data: &[u8]) {
-        match self
-            .conn_write
-            .as_mut()
-            .unwrap()
-            .send(WssMessage::from(data))
+    // Wait for the authentication response pack, the packs before it are handled as usual.
+    async fn wait_auth_resp(&mut self) -> Result<()> {
+        self.auth_result = None;
+        let wait = async {
+            loop {
+                let msg = self.read().await?;
+                self.handle_data(&msg).await?;
+                if let Some(res) = self.auth_result.take() {
+                    return res;
+                }
+            }
+        };
+
+        tokio::time::timeout(AUTH_TIMEOUT, wait)
             .await
-        {
-            Err(e) => eprintln!("{:#?}", e),
-            _ => {}
+            .unwrap_or(Err(Error::AuthTimeout))
+    }
+
+    // Reconnect to the next danmu host server until it succeeds.
+    // The token and host list are fetched again before every attempt,
+    // and attempts are delayed with exponential backoff and jitter.
+    // The status is sent to the channel as LiveEvent::Reconnecting and LiveEvent::Reconnected.
+    // It only fails when the receiver of live events is dropped.
+    async fn reconnect(&mut self, reason: Error) -> Result<()> {
+        self.conn_read = None;
+        let mut reason = reason;
+        let mut retries = 0;
+        loop {
+            self.emit(LiveEvent::Reconnecting(Reconnecting {
+                retries,
+                reason: reason.to_string(),
+            }))
+            .await?;
+            tokio::time::sleep(utils::backoff_delay(retries)).await;
+            retries += 1;
+
+            if let Err(e) = self.init_client().await {
+                reason = e;
+                continue;
+            }
+
+            match self.connect_hosts(self.host_index as usize + 1).await {
+                Ok(()) => return self.emit(LiveEvent::Reconnected).await,
+                Err(Error::ChannelClosed) => return Err(Error::ChannelClosed),
+                Err(e) => reason = e,
+            }
         }
     }
 
-    async fn read(&mut self) -> Vec<u8> {
-        let res = self
-            .conn_read
-            .as_mut()
-            .unwrap()
-            .next()
-            .await
-            .unwrap()
-            .unwrap()
-            .into_data();
+    async fn send(&mut self, data: &[u8]) -> Result<()> {
+        let conn_write = self.conn_write.as_ref().ok_or(Error::Closed)?;
+        conn_write.lock().await.send(WssMessage::from(data)).await?;
 
-        res
+        Ok(())
+    }
+
+    // Send the event to the channel.
+    async fn emit(&mut self, event: LiveEvent) -> Result<()> {
+        let mpsc_tx = self.mpsc_tx.as_ref().ok_or(Error::ChannelClosed)?;
+        mpsc_tx.send(event).await.map_err(|_| Error::ChannelClosed)
     }
 
-    pub async fn send_auth(&mut self) {
-        let auth_pack_body =
-            // If 'protover' is 2, the response pack will be compressed by 'zlib'.
-            AuthPack::new(0, self.room_id, 2, "web".to_owned(), 2, self.token.clone());
-        let ser_body = serde_json::to_vec(&auth_pack_body).unwrap();
-        let mut auth_pack: Vec<u8> = vec![0; ser_body.len() + 16];
-
-        utils::fill_datapack_header(DataPack::Auth, auth_pack.as_mut_slice(), 1);
-        let mut offset = 16;
-        for byte in ser_body.as_slice() {
-            auth_pack[offset] = *byte;
-            offset += 1;
+    // If the connection is closed or broken, return the error.
+    async fn read(&mut self) -> Result<Vec<u8>> {
+        match self.conn_read.as_mut().ok_or(Error::Closed)?.next().await {
+            Some(Ok(WssMessage::Close(_))) | None => Err(Error::Closed),
+            Some(Ok(msg)) => Ok(msg.into_data()),
+            Some(Err(e)) => Err(e.into()),
         }
+    }
 
-        self.send(&auth_pack).await;
+    pub async fn send_auth(&mut self) -> Result<()> {
+        // If 'protover' is 2, the response pack will be compressed by 'zlib'.
+        // If 'protover' is 3, the response pack will be compressed by 'brotli'.
+        // If 'uid' is 0, the server will treat the client as an anonymous viewer.
+        let auth_pack_body = AuthPack::new(
+            self.account.mid as u64,
+            self.room_id,
+            self.proto_ver,
+            "web".to_owned(),
+            2,
+            self.token.clone(),
+            self.credential.buvid3.clone(),
+        );
+        let auth_pack =
+            Packet::new(Operation::Auth, PROTO_VER_INT, auth_pack_body.into_bytes()).into_bytes();
+
+        self.send(&auth_pack).await
     }
 
-    pub async fn send_heart_beat(&mut self) {
-        let mut beat_pack: Vec<u8> = vec![0; 16];
-        utils::fill_datapack_header(DataPack::HeartBeat, beat_pack.as_mut_slice(), 1);
-        self.send(&beat_pack).await;
+    pub async fn send_heart_beat(&mut self) -> Result<()> {
+        let beat_pack = Packet::new(Operation::HeartBeat, PROTO_VER_INT, vec![]).into_bytes();
+        self.send(&beat_pack).await
     }
 
-    pub async fn connect(
-        &mut self,
-    ) -> Result<SplitSink<WebSocketStream, WssMessage>, reqwest::Error> {
+    // Send a heart beat pack through the writer returned by connect(),
+    // without locking the client, which is usually blocked on receiving.
+    pub async fn send_heart_beat_to(conn_write: &WebSocketWriter) -> Result<()> {
+        let beat_pack = Packet::new(Operation::HeartBeat, PROTO_VER_INT, vec![]).into_bytes();
+        conn_write
+            .lock()
+            .await
+            .send(WssMessage::from(beat_pack))
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn connect(&mut self) -> Result<WebSocketWriter> {
         // initialize danmu client
         self.init_client().await?;
 
-        // shake hands
-        self.shake_hands().await;
+        // shake hands, send authentication pack and wait for the response
+        self.connect_hosts(0).await?;
 
-        // send authentication pack
-        self.send_auth().await;
+        self.conn_write.clone().ok_or(Error::NoHostAvailable)
+    }
 
-        if let Some(conn_write) = self.conn_write.take() {
-            Ok(conn_write)
-        } else {
-            panic!("Connecting bilibili danmaku server failed.");
+    // Receive and handle the next message, reconnect if the connection is lost.
+    // It only fails when the receiver of live events is dropped.
+    pub async fn receive(&mut self) -> Result<()> {
+        match self.read().await {
+            Ok(msg) => self.handle_data(&msg).await,
+            // the connection is lost, then need to reconnecting
+            Err(e) => self.reconnect(e).await,
         }
     }
 
-    pub async fn receive(&mut self) {
-        let msg = self.read().await;
-        if msg.len() >= 16 {
-            if msg[7] == 2 {
-                // data compressed by zlib, then need to decompressing
-                let dec_data = utils::zlib_dec(&msg[16..]).unwrap();
-                let packs = utils::split_packs(&dec_data);
-                for p in packs {
-                    self.handle_msg(p.as_slice()).await;
-                }
+    // Malformed packs are skipped and sent to the channel as LiveEvent::Unknown,
+    // so it only fails when the receiver of live events is dropped.
+    async fn handle_data(&mut self, msg: &[u8]) -> Result<()> {
+        let res = self.decoder.feed(msg);
+
+        while let Some(frame) = self.decoder.next_frame() {
+            match frame {
+                Frame::Command(body) => self.handle_msg(&body).await?,
+                Frame::HeartBeatResp(popularity) => self.handle_heart_beat_resp(popularity).await?,
+                Frame::AuthResp(body) => self.handle_auth_resp(&body).await,
+                Frame::Other(_) => {}
             }
         }
+
+        if let Err(e) = res {
+            let raw = RawCommand {
+                cmd: MALFORMED_PACK_CMD.to_owned(),
+                raw: Error::from(e).to_string(),
+            };
+            self.emit(LiveEvent::Unknown(raw)).await?;
+        }
+
+        Ok(())
     }
 
-    async fn handle_msg(&mut self, msg: &[u8]) {
-        let json: serde_json::Value = serde_json::from_slice(msg).unwrap();
-        match json["cmd"].to_string().as_str() {
-            "\"DANMU_MSG\"" => {
-                let content = json["info"][1].to_string();
-                let author = json["info"][2][1].to_string();
-                let datetime = utils::timestamp_to_datetime_utc8(
-                    json["info"][9]["ts"].to_string().parse().unwrap(),
-                );
-                let msg = Message::new(
-                    MessageKind::DANMU_MSG,
-                    content[1..content.len() - 1].to_owned(),
-                    author[1..author.len() - 1].to_owned(),
-                    datetime,
-                );
-                /* Send Message to Channel */
-                self.mpsc_tx.as_mut().unwrap().send(msg).await.unwrap();
-            }
+    // The body of heart beat response pack is the popularity of the live room.
+    async fn handle_heart_beat_resp(&mut self, popularity: u32) -> Result<()> {
+        self.popularity = popularity;
+        /* Send Event to Channel */
+        self.emit(LiveEvent::Popularity(self.popularity)).await
+    }
 
-            "\"SUPER_CHAT_MESSAGE\"" => {
-                let content = json["data"]["message"]
-                    .to_string()
-                    .trim_start_matches("\"")
-                    .trim_end_matches("\"")
-                    .to_owned();
-                let mut author = json["data"]["user_info"]["uname"]
-                    .to_string()
-                    .trim_start_matches("\"")
-                    .trim_end_matches("\"")
-                    .to_owned();
-                // separator: ","
-                // format: "username,sc_duration,endtime"
-                author = author
-                    + ","
-                    + json["data"]["time"].to_string().as_str()
-                    + ","
-                    + json["data"]["end_time"].to_string().as_str();
-                let datetime = utils::timestamp_to_datetime_utc8(
-                    json["data"]["start_time"].to_string().parse().unwrap(),
-                );
-                let msg = Message::new(MessageKind::SUPER_CHAT_MESSAGE, content, author, datetime);
-                /* Send Message to Channel */
-                self.mpsc_tx.as_mut().unwrap().send(msg).await.unwrap();
-            }
+    // The body of authentication response pack is a JSON like '{"code":0}'.
+    async fn handle_auth_resp(&mut self, body: &[u8]) {
+        self.auth_result = match AuthRespPack::from_bytes(body) {
+            Ok(resp) if resp.code == 0 => Some(Ok(())),
+            Ok(resp) => Some(Err(Error::AuthRejected(resp.code))),
+            Err(_) => Some(Err(Error::AuthInvalidResp)),
+        };
+    }
 
-            "\"COMBO_SEND\"" => {}
-            "\"SEND_GIFT\"" => {
-                let action = json["data"]["action"]
-                    .to_string()
-                    .trim_start_matches("\"")
-                    .trim_end_matches("\"")
-                    .to_owned();
-                let gift_name = json["data"]["giftName"]
-                    .to_string()
-                    .trim_start_matches("\"")
-                    .trim_end_matches("\"")
-                    .to_owned();
-                let gift_num = json["data"]["num"].to_string();
-                let uname = json["data"]["uname"]
-                    .to_string()
-                    .trim_start_matches("\"")
-                    .trim_end_matches("\"")
-                    .to_owned();
-                let datetime = utils::timestamp_to_datetime_utc8(
-                    json["data"]["timestamp"].to_string().parse().unwrap(),
-                );
-                // TODO: implment i18n
-                let msg = Message::new(
-                    MessageKind::SEND_GIFT,
-                    action + "了" + gift_num.as_str() + "个" + gift_name.as_str(),
-                    uname,
-                    datetime,
-                );
-                /* Send Message to Channel */
-                self.mpsc_tx.as_mut().unwrap().send(msg).await.unwrap();
-            }
-            "\"INTERACT_WORD\"" => {}
-            "\"NOTICE_MSG\"" => {}
-            _ => {}
+    // The body of normal pack is a raw JSON command.
+    async fn handle_msg(&mut self, body: &[u8]) -> Result<()> {
+        let event = LiveEvent::from_body(body);
+        if let (LiveEvent::Unknown(raw), Some(file)) = (&event, self.dump_file.as_mut()) {
+            let _ = writeln!(file, "{}", raw.raw);
         }
+
+        /* Send Event to Channel */
+        self.emit(event).await
     }
 }
 
 impl AuthPack {
     pub fn new(
-        _uid: u32,
+        _uid: u64,
         _room_id: u32,
         _proto_ver: u8,
         _platform: String,
         _type: u32,
         _key: String,
+        _buvid: String,
     ) -> Self {
         Self {
             _uid,
@@ -306,6 +402,7 @@ impl AuthPack {
             _platform,
             _type,
             _key,
+            _buvid,
         }
     }
 }
@@ -321,3 +418,34 @@ impl Pack for AuthRespPack {
         serde_json::to_vec(&self).unwrap()
     }
 }
+
+#[test]
+fn test_auth_pack_serde() {
+    let pack = AuthPack::new(
+        1,
+        2,
+        3,
+        "web".to_owned(),
+        2,
+        "key".to_owned(),
+        "".to_owned(),
+    );
+    assert_eq!(
+        String::from_utf8(pack.into_bytes()).unwrap(),
+        r#"{"uid":1,"roomid":2,"protover":3,"platform":"web","type":2,"key":"key"}"#
+    );
+
+    let pack = AuthPack::new(
+        1,
+        2,
+        3,
+        "web".to_owned(),
+        2,
+        "key".to_owned(),
+        "b".to_owned(),
+    );
+    assert_eq!(
+        String::from_utf8(pack.into_bytes()).unwrap(),
+        r#"{"uid":1,"roomid":2,"protover":3,"platform":"web","type":2,"key":"key","buvid":"b"}"#
+    );
+}
@@ -0,0 +1,432 @@
+use std::{collections::VecDeque, fmt};
+
+use crate::client::{
+    Operation, Packet, PacketHeader, HEADER_LEN, PROTO_VER_BROTLI, PROTO_VER_ZLIB,
+};
+use crate::utils;
+
+// Packs longer than this are treated as malformed instead of being buffered,
+// and so are compressed batches which decompress to more than this.
+pub const MAX_PACKET_LEN: usize = 16 * 1024 * 1024;
+
+// How deep compressed batches may be nested, the server only nests them once.
+const MAX_NESTING: usize = 4;
+
+// A frame decoded from the danmaku stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    // The body of a normal pack, which is a JSON command
+    Command(Vec<u8>),
+    // The popularity carried by a heart beat response
+    HeartBeatResp(u32),
+    // The body of an authentication response, which is a JSON like '{"code":0}'
+    AuthResp(Vec<u8>),
+    // A pack of any other operation
+    Other(Packet),
+}
+
+// Why the danmaku stream can not be decoded.
+#[derive(Debug)]
+pub enum DecodeError {
+    // The header length is shorter than HEADER_LEN
+    HeaderTooShort(usize),
+    // The packet length is shorter than the header length
+    PacketTooShort {
+        packet_len: usize,
+        header_len: usize,
+    },
+    // The packet length is longer than MAX_PACKET_LEN
+    PacketTooLong(usize),
+    // Compressed batches are nested deeper than MAX_NESTING
+    NestedTooDeep,
+    // The body of a compressed pack can not be decompressed within MAX_PACKET_LEN
+    Decompress(std::io::Error),
+    // The body of a heart beat response is shorter than 4 bytes
+    HeartBeatRespTooShort,
+    // A decompressed batch ends with an incomplete header or pack
+    TruncatedBatch,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeaderTooShort(header_len) => {
+                write!(f, "header length {} is too short", header_len)
+            }
+            Self::PacketTooShort {
+                packet_len,
+                header_len,
+            } => write!(
+                f,
+                "packet length {} is shorter than the header length {}",
+                packet_len, header_len
+            ),
+            Self::PacketTooLong(packet_len) => {
+                write!(f, "packet length {} is too long", packet_len)
+            }
+            Self::NestedTooDeep => write!(f, "compressed packs are nested too deep"),
+            Self::Decompress(e) => write!(f, "decompressing pack: {}", e),
+            Self::HeartBeatRespTooShort => write!(f, "heart beat response is too short"),
+            Self::TruncatedBatch => write!(f, "truncated pack in a compressed batch"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decompress(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// An I/O-free decoder of the danmaku stream.
+//
+// It is fed with the bytes read from the connection, in pieces of any size,
+// and yields the frames of the complete packs. Compressed batches are
+// unwrapped recursively. An incomplete pack is kept until the rest of it is fed.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buf: Vec<u8>,
+    frames: VecDeque<Frame>,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Decode the complete packs in the buffered bytes and 'data'.
+    // On a malformed pack, the buffered bytes are dropped since the stream can not
+    // be resynchronized, while the frames decoded before it are still yielded.
+    pub fn feed(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        self.buf.extend_from_slice(data);
+
+        let mut start = 0;
+        let res = loop {
+            let header = match PacketHeader::decode(&self.buf[start..]) {
+                Some(header) => header,
+                None => break Ok(()),
+            };
+            if let Err(e) = check_lengths(&header) {
+                break Err(e);
+            }
+            let packet_len = header.packet_len as usize;
+            if start + packet_len > self.buf.len() {
+                break Ok(());
+            }
+
+            let body = &self.buf[start + header.header_len as usize..start + packet_len];
+            if let Err(e) = decode_pack(header, body, 0, &mut self.frames) {
+                break Err(e);
+            }
+            start += packet_len;
+        };
+
+        match res {
+            Ok(()) => {
+                self.buf.drain(..start);
+            }
+            Err(_) => self.buf.clear(),
+        }
+
+        res
+    }
+
+    // Pop the next decoded frame.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.frames.pop_front()
+    }
+
+    // Length of the incomplete pack kept in the buffer.
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+fn check_lengths(header: &PacketHeader) -> Result<(), DecodeError> {
+    let (header_len, packet_len) = (header.header_len as usize, header.packet_len as usize);
+    if header_len < HEADER_LEN {
+        return Err(DecodeError::HeaderTooShort(header_len));
+    }
+    if packet_len < header_len {
+        return Err(DecodeError::PacketTooShort {
+            packet_len,
+            header_len,
+        });
+    }
+    if packet_len > MAX_PACKET_LEN {
+        return Err(DecodeError::PacketTooLong(packet_len));
+    }
+
+    Ok(())
+}
+
+fn decode_pack(
+    header: PacketHeader,
+    body: &[u8],
+    nesting: usize,
+    frames: &mut VecDeque<Frame>,
+) -> Result<(), DecodeError> {
+    let frame = match (header.opcode, header.version) {
+        (Operation::Normal, PROTO_VER_ZLIB | PROTO_VER_BROTLI) => {
+            if nesting >= MAX_NESTING {
+                return Err(DecodeError::NestedTooDeep);
+            }
+            let batch = match header.version {
+                PROTO_VER_ZLIB => utils::zlib_dec(body, MAX_PACKET_LEN),
+                _ => utils::brotli_dec(body, MAX_PACKET_LEN),
+            }
+            .map_err(DecodeError::Decompress)?;
+
+            return decode_batch(&batch, nesting + 1, frames);
+        }
+        (Operation::Normal, _) => Frame::Command(body.to_vec()),
+        (Operation::HeartBeatResp, _) => match body.get(0..4) {
+            Some(bytes) => Frame::HeartBeatResp(u32::from_be_bytes(bytes.try_into().unwrap())),
+            None => return Err(DecodeError::HeartBeatRespTooShort),
+        },
+        (Operation::AuthResp, _) => Frame::AuthResp(body.to_vec()),
+        _ => Frame::Other(Packet {
+            header,
+            body: body.to_vec(),
+        }),
+    };
+    frames.push_back(frame);
+
+    Ok(())
+}
+
+// A decompressed batch is complete, so a truncated pack in it is malformed.
+fn decode_batch(
+    batch: &[u8],
+    nesting: usize,
+    frames: &mut VecDeque<Frame>,
+) -> Result<(), DecodeError> {
+    let mut start = 0;
+    while start < batch.len() {
+        let header = PacketHeader::decode(&batch[start..]).ok_or(DecodeError::TruncatedBatch)?;
+        check_lengths(&header)?;
+        let packet_len = header.packet_len as usize;
+        if start + packet_len > batch.len() {
+            return Err(DecodeError::TruncatedBatch);
+        }
+
+        let body = &batch[start + header.header_len as usize..start + packet_len];
+        decode_pack(header, body, nesting, frames)?;
+        start += packet_len;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn zlib(data: &[u8]) -> Vec<u8> {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let mut enc = ZlibEncoder::new(vec![], Compression::default());
+    enc.write_all(data).unwrap();
+    enc.finish().unwrap()
+}
+
+#[cfg(test)]
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    brotli::BrotliCompress(&mut &data[..], &mut out, &Default::default()).unwrap();
+    out
+}
+
+#[cfg(test)]
+fn pack(opcode: Operation, version: u16, body: &[u8]) -> Vec<u8> {
+    Packet::new(opcode, version, body.to_vec()).into_bytes()
+}
+
+#[test]
+fn test_decoder_frames() {
+    use crate::client::{PROTO_VER_INT, PROTO_VER_JSON};
+
+    let a = pack(Operation::Normal, PROTO_VER_JSON, b"{\"cmd\":\"A\"}");
+    let b = pack(Operation::Normal, PROTO_VER_JSON, b"{\"cmd\":\"B\"}");
+    let batch = [a.clone(), b.clone()].concat();
+    let nested = pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&batch));
+    let command = |cmd: &str| Frame::Command(format!("{{\"cmd\":\"{}\"}}", cmd).into_bytes());
+
+    let cases: Vec<(&str, Vec<u8>, Vec<Frame>)> = vec![
+        ("empty", vec![], vec![]),
+        ("json command", a.clone(), vec![command("A")]),
+        (
+            "packs one after another",
+            [a.clone(), b.clone()].concat(),
+            vec![command("A"), command("B")],
+        ),
+        (
+            "heart beat response",
+            pack(Operation::HeartBeatResp, PROTO_VER_INT, &[0, 0, 1, 0]),
+            vec![Frame::HeartBeatResp(256)],
+        ),
+        (
+            "auth response",
+            pack(Operation::AuthResp, PROTO_VER_INT, b"{\"code\":0}"),
+            vec![Frame::AuthResp(b"{\"code\":0}".to_vec())],
+        ),
+        (
+            "zlib batch",
+            pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&batch)),
+            vec![command("A"), command("B")],
+        ),
+        (
+            "brotli batch",
+            pack(Operation::Normal, PROTO_VER_BROTLI, &brotli(&batch)),
+            vec![command("A"), command("B")],
+        ),
+        (
+            "nested batch",
+            pack(Operation::Normal, PROTO_VER_BROTLI, &brotli(&nested)),
+            vec![command("A"), command("B")],
+        ),
+        (
+            "other operation",
+            pack(Operation::Unknown(9), PROTO_VER_INT, b"?"),
+            vec![Frame::Other(Packet::new(
+                Operation::Unknown(9),
+                PROTO_VER_INT,
+                b"?".to_vec(),
+            ))],
+        ),
+    ];
+
+    for (name, data, expected) in cases {
+        let mut decoder = Decoder::new();
+        decoder.feed(&data).unwrap();
+        let frames: Vec<_> = std::iter::from_fn(|| decoder.next_frame()).collect();
+        assert_eq!(frames, expected, "{}", name);
+        assert_eq!(decoder.buffered_len(), 0, "{}", name);
+    }
+}
+
+#[test]
+fn test_decoder_partial_feed() {
+    let data = [
+        pack(Operation::Normal, 0, b"{\"cmd\":\"A\"}"),
+        pack(Operation::Normal, 0, b"{\"cmd\":\"B\"}"),
+    ]
+    .concat();
+
+    // feed the packs byte by byte
+    let mut decoder = Decoder::new();
+    let mut frames = vec![];
+    for byte in data.iter() {
+        decoder.feed(&[*byte]).unwrap();
+        frames.extend(std::iter::from_fn(|| decoder.next_frame()));
+    }
+    assert_eq!(frames.len(), 2);
+
+    // a truncated pack is kept until the rest of it is fed
+    let mut decoder = Decoder::new();
+    decoder.feed(&data[..data.len() - 3]).unwrap();
+    assert_eq!(
+        decoder.next_frame(),
+        Some(Frame::Command(b"{\"cmd\":\"A\"}".to_vec()))
+    );
+    assert_eq!(decoder.next_frame(), None);
+    assert_eq!(decoder.buffered_len(), 24);
+    decoder.feed(&data[data.len() - 3..]).unwrap();
+    assert_eq!(
+        decoder.next_frame(),
+        Some(Frame::Command(b"{\"cmd\":\"B\"}".to_vec()))
+    );
+}
+
+#[test]
+fn test_decoder_malformed() {
+    let a = pack(Operation::Normal, 0, b"{\"cmd\":\"A\"}");
+    let header = |header_len: u16, packet_len: u32| {
+        let mut header = PacketHeader::new(Operation::Normal, 0, 0, 1);
+        (header.header_len, header.packet_len) = (header_len, packet_len);
+        header.encode().to_vec()
+    };
+    let truncated_batch = {
+        let mut batch = a.clone();
+        batch.truncate(a.len() - 1);
+        batch
+    };
+    // zeros decompressed to one byte more than MAX_PACKET_LEN
+    let bomb = vec![0; MAX_PACKET_LEN + 1];
+    let brotli_bomb = {
+        use std::io::Write;
+
+        let mut out = vec![];
+        let mut w = brotli::CompressorWriter::new(&mut out, 4096, 1, 22);
+        w.write_all(&bomb).unwrap();
+        drop(w);
+        out
+    };
+    let mut nested = a.clone();
+    for _ in 0..=MAX_NESTING {
+        nested = pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&nested));
+    }
+
+    let cases: Vec<(&str, Vec<u8>)> = vec![
+        ("header length too short", header(8, 32)),
+        ("packet length shorter than header", header(16, 8)),
+        (
+            "packet length too long",
+            header(16, MAX_PACKET_LEN as u32 + 1),
+        ),
+        (
+            "heart beat response too short",
+            pack(Operation::HeartBeatResp, 1, &[0, 1]),
+        ),
+        (
+            "invalid zlib",
+            pack(Operation::Normal, PROTO_VER_ZLIB, b"not zlib"),
+        ),
+        (
+            "invalid brotli",
+            pack(Operation::Normal, PROTO_VER_BROTLI, b"not brotli"),
+        ),
+        (
+            "truncated pack in batch",
+            pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&truncated_batch)),
+        ),
+        (
+            "truncated header in batch",
+            pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&a[..8])),
+        ),
+        ("nested too deep", nested),
+        (
+            "zlib bomb",
+            pack(Operation::Normal, PROTO_VER_ZLIB, &zlib(&bomb)),
+        ),
+        (
+            "brotli bomb",
+            pack(Operation::Normal, PROTO_VER_BROTLI, &brotli_bomb),
+        ),
+    ];
+
+    for (name, data) in cases {
+        // the frames before the malformed pack are still yielded
+        let mut decoder = Decoder::new();
+        assert!(
+            decoder.feed(&[a.clone(), data].concat()).is_err(),
+            "{}",
+            name
+        );
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Frame::Command(b"{\"cmd\":\"A\"}".to_vec())),
+            "{}",
+            name
+        );
+        assert_eq!(decoder.next_frame(), None, "{}", name);
+        assert_eq!(decoder.buffered_len(), 0, "{}", name);
+    }
+
+    let mut decoder = Decoder::new();
+    assert!(matches!(
+        decoder.feed(&header(8, 32)),
+        Err(DecodeError::HeaderTooShort(8))
+    ));
+}
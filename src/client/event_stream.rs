@@ -0,0 +1,113 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::{SinkExt, Stream};
+use tokio::{
+    sync::{mpsc, Notify},
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::protocol::Message as WssMessage;
+
+use crate::client::{DanmakuClient, WebSocketWriter};
+use crate::config::Config;
+use crate::{Error, LiveEvent, Result};
+
+// The live events of a room as a stream, without channels or locks outside.
+//
+// The client runs in a task of its own and reconnects by itself, so the stream
+// only ends after EventSender::close(), or with the error the client stops with.
+#[derive(Debug)]
+pub struct EventStream {
+    rx: mpsc::Receiver<LiveEvent>,
+    task: Option<JoinHandle<Result<()>>>,
+}
+
+// The writing side of the connection opened by EventStream::connect().
+// Heart beats are not sent by the stream, send one every 30 seconds to keep the connection.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    conn_write: WebSocketWriter,
+    close: Arc<Notify>,
+}
+
+impl EventStream {
+    pub async fn connect(room_id: u32, config: &Config) -> Result<(Self, EventSender)> {
+        let (tx, rx) = mpsc::channel(512);
+        let mut client = DanmakuClient::new(room_id, config, tx);
+        let conn_write = client.connect().await?;
+
+        let close = Arc::new(Notify::new());
+        let close_notified = close.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    res = client.receive() => res?,
+                    _ = close_notified.notified() => return Ok(()),
+                }
+            }
+        });
+
+        let stream = Self {
+            rx,
+            task: Some(task),
+        };
+        let sender = EventSender { conn_write, close };
+
+        Ok((stream, sender))
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<LiveEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(event) = futures::ready!(self.rx.poll_recv(cx)) {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        // the client is stopped, yield the error it stops with
+        let task = match self.task.as_mut() {
+            Some(task) => task,
+            None => return Poll::Ready(None),
+        };
+        let res = futures::ready!(Pin::new(task).poll(cx));
+        self.task = None;
+        match res {
+            Ok(Err(Error::ChannelClosed)) | Ok(Ok(())) => Poll::Ready(None),
+            Ok(Err(e)) => Poll::Ready(Some(Err(e))),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => Poll::Ready(None),
+        }
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.as_ref() {
+            task.abort();
+        }
+    }
+}
+
+impl EventSender {
+    pub async fn heart_beat(&self) -> Result<()> {
+        DanmakuClient::send_heart_beat_to(&self.conn_write).await
+    }
+
+    // Close the connection, then the stream ends instead of reconnecting.
+    pub async fn close(&self) -> Result<()> {
+        // stop the client before the server closes the connection
+        self.close.notify_one();
+        self.conn_write
+            .lock()
+            .await
+            .send(WssMessage::Close(None))
+            .await?;
+
+        Ok(())
+    }
+}
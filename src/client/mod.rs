@@ -1,5 +1,13 @@
 mod account;
 mod danmu_client;
+mod decoder;
+mod event_stream;
+mod multi_room_client;
+mod packet;
 
 pub use account::*;
 pub use danmu_client::*;
+pub use decoder::*;
+pub use event_stream::*;
+pub use multi_room_client::*;
+pub use packet::*;
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use tokio::{
+    sync::{mpsc::Sender, Notify},
+    task::JoinHandle,
+};
+
+use crate::client::EventStream;
+use crate::config::Config;
+use crate::{Result, RoomEvent};
+
+// How often the heart beat pack is sent to keep a connection alive.
+const HEART_BEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+// A client which subscribes to several live rooms at once.
+// Every room has its own websocket connection, and all the events are
+// sent to a single channel, tagged with the room they come from.
+#[derive(Debug)]
+pub struct MultiRoomClient {
+    config: Config,
+    mpsc_tx: Sender<RoomEvent>,
+    rooms: HashMap<u32, RoomTasks>,
+}
+
+// The tasks running for a subscribed room, aborted on unsubscribing.
+// The client of the room stops when its stream is dropped by the aborted 'forward'.
+#[derive(Debug)]
+struct RoomTasks {
+    heart_beat: JoinHandle<()>,
+    forward: JoinHandle<()>,
+}
+
+impl RoomTasks {
+    fn abort(&self) {
+        self.heart_beat.abort();
+        self.forward.abort();
+    }
+}
+
+impl MultiRoomClient {
+    pub fn new(config: &Config, mpsc_tx: Sender<RoomEvent>) -> Self {
+        Self {
+            config: config.clone(),
+            mpsc_tx,
+            rooms: HashMap::new(),
+        }
+    }
+
+    // Connect to the room and start receiving its events.
+    // Subscribing to a room twice does nothing.
+    pub async fn subscribe(&mut self, room_id: u32) -> Result<()> {
+        if self.rooms.contains_key(&room_id) {
+            return Ok(());
+        }
+
+        let tasks = Self::spawn_room(room_id, &self.config, self.mpsc_tx.clone()).await?;
+        self.rooms.insert(room_id, tasks);
+
+        Ok(())
+    }
+
+    // Connect to the rooms concurrently, return the results in the order of 'room_ids'.
+    pub async fn subscribe_all(&mut self, room_ids: &[u32]) -> Vec<Result<()>> {
+        let connecting = room_ids
+            .iter()
+            .map(|&room_id| Self::spawn_room(room_id, &self.config, self.mpsc_tx.clone()));
+        let results = futures::future::join_all(connecting).await;
+
+        room_ids
+            .iter()
+            .zip(results)
+            .map(|(&room_id, res)| {
+                let tasks = res?;
+                // the room may appear twice in 'room_ids'
+                if let Some(old) = self.rooms.insert(room_id, tasks) {
+                    old.abort();
+                }
+                Ok(())
+            })
+            .collect()
+    }
+
+    // Close the connection to the room. Return false if it is not subscribed.
+    pub fn unsubscribe(&mut self, room_id: u32) -> bool {
+        match self.rooms.remove(&room_id) {
+            Some(tasks) => {
+                tasks.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn room_ids(&self) -> Vec<u32> {
+        self.rooms.keys().copied().collect()
+    }
+
+    async fn spawn_room(
+        room_id: u32,
+        config: &Config,
+        mpsc_tx: Sender<RoomEvent>,
+    ) -> Result<RoomTasks> {
+        let (mut stream, sender) = EventStream::connect(room_id, config).await?;
+
+        // notified when 'forward' ends, then the heart beats stop too
+        let stopped = Arc::new(Notify::new());
+        let heart_beat_stopped = stopped.clone();
+        let heart_beat = tokio::spawn(async move {
+            loop {
+                // It fails while the client is reconnecting, which is already
+                // sent to the channel as LiveEvent::Reconnecting.
+                let _ = sender.heart_beat().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(HEART_BEAT_INTERVAL) => {}
+                    _ = heart_beat_stopped.notified() => return,
+                }
+            }
+        });
+        let forward = tokio::spawn(async move {
+            // The client reconnects by itself, so the stream only ends
+            // when the client stops, and the errors are not recoverable.
+            while let Some(Ok(event)) = stream.next().await {
+                if mpsc_tx.send(RoomEvent { room_id, event }).await.is_err() {
+                    break;
+                }
+            }
+            stopped.notify_one();
+        });
+
+        Ok(RoomTasks {
+            heart_beat,
+            forward,
+        })
+    }
+}
+
+impl Drop for MultiRoomClient {
+    fn drop(&mut self) {
+        for tasks in self.rooms.values() {
+            tasks.abort();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Length of the header in front of every pack.
+pub const HEADER_LEN: usize = 16;
+
+// Protocol versions carried by the header of a pack.
+pub const PROTO_VER_JSON: u16 = 0; /* Body is a raw JSON command */
+pub const PROTO_VER_INT: u16 = 1; /* Body is a number (heart beat) or a raw JSON (auth) */
+pub const PROTO_VER_ZLIB: u16 = 2; /* Body is a batch of packs compressed by 'zlib' */
+pub const PROTO_VER_BROTLI: u16 = 3; /* Body is a batch of packs compressed by 'brotli' */
+
+// Operation code carried by the header of a pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    HeartBeat,     /* 2: Heart Beat Pack */
+    HeartBeatResp, /* 3: Heart Beat Response Pack */
+    Normal,        /* 5: Normal Pack (commands) */
+    Auth,          /* 7: Authentication Pack */
+    AuthResp,      /* 8: Authentication Response Pack */
+    Unknown(u32),  /* Any other operation code */
+}
+
+impl From<u32> for Operation {
+    fn from(op_code: u32) -> Self {
+        match op_code {
+            2 => Self::HeartBeat,
+            3 => Self::HeartBeatResp,
+            5 => Self::Normal,
+            7 => Self::Auth,
+            8 => Self::AuthResp,
+            _ => Self::Unknown(op_code),
+        }
+    }
+}
+
+impl From<Operation> for u32 {
+    fn from(op: Operation) -> Self {
+        match op {
+            Operation::HeartBeat => 2,
+            Operation::HeartBeatResp => 3,
+            Operation::Normal => 5,
+            Operation::Auth => 7,
+            Operation::AuthResp => 8,
+            Operation::Unknown(op_code) => op_code,
+        }
+    }
+}
+
+// The 16-byte big-endian header in front of every pack.
+//
+// | offset | size | field      |
+// |--------|------|------------|
+// | 0      | 4    | packet_len |
+// | 4      | 2    | header_len |
+// | 6      | 2    | version    |
+// | 8      | 4    | opcode     |
+// | 12     | 4    | sequence   |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    pub packet_len: u32, /* Length of the whole pack, including the header */
+    pub header_len: u16, /* Length of the header, always 16 */
+    pub version: u16,    /* Protocol version of the body */
+    pub opcode: Operation,
+    pub sequence: u32,
+}
+
+impl PacketHeader {
+    pub fn new(opcode: Operation, version: u16, body_len: usize, sequence: u32) -> Self {
+        Self {
+            packet_len: (HEADER_LEN + body_len) as u32,
+            header_len: HEADER_LEN as u16,
+            version,
+            opcode,
+            sequence,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut buf = [0; HEADER_LEN];
+        buf[0..4].copy_from_slice(&self.packet_len.to_be_bytes());
+        buf[4..6].copy_from_slice(&self.header_len.to_be_bytes());
+        buf[6..8].copy_from_slice(&self.version.to_be_bytes());
+        buf[8..12].copy_from_slice(&u32::from(self.opcode).to_be_bytes());
+        buf[12..16].copy_from_slice(&self.sequence.to_be_bytes());
+
+        buf
+    }
+
+    // If 'data' is shorter than a header, return None.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_LEN {
+            return None;
+        }
+
+        Some(Self {
+            packet_len: u32::from_be_bytes(data[0..4].try_into().unwrap()),
+            header_len: u16::from_be_bytes(data[4..6].try_into().unwrap()),
+            version: u16::from_be_bytes(data[6..8].try_into().unwrap()),
+            opcode: Operation::from(u32::from_be_bytes(data[8..12].try_into().unwrap())),
+            sequence: u32::from_be_bytes(data[12..16].try_into().unwrap()),
+        })
+    }
+}
+
+// A pack with its header and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub header: PacketHeader,
+    pub body: Vec<u8>,
+}
+
+impl Packet {
+    pub fn new(opcode: Operation, version: u16, body: Vec<u8>) -> Self {
+        Self {
+            header: PacketHeader::new(opcode, version, body.len(), 1),
+            body,
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_LEN + self.body.len());
+        data.extend_from_slice(&self.header.encode());
+        data.extend(self.body);
+
+        data
+    }
+}
+
+#[test]
+fn test_packet_header_codec() {
+    let header = PacketHeader::new(Operation::Auth, 1, 16, 1);
+    let expected: [u8; HEADER_LEN] = [0, 0, 0, 32, 0, 16, 0, 1, 0, 0, 0, 7, 0, 0, 0, 1];
+
+    assert_eq!(header.encode(), expected);
+    assert_eq!(PacketHeader::decode(&expected), Some(header));
+    assert_eq!(PacketHeader::decode(&expected[..15]), None);
+}
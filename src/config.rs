@@ -1,22 +1,55 @@
 use serde::{Deserialize, Serialize};
-use std::{fs, io::Error};
+use std::fs;
 
-use crate::Credential;
+use crate::{Credential, Result};
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub credential: Credential,
+    // Protocol version sent in the authentication pack.
+    // 2: the body of packs is compressed by 'zlib'
+    // 3: the body of packs is compressed by 'brotli'
+    #[serde(default = "default_proto_ver")]
+    pub proto_ver: u8,
+    // Path of a file which the raw unknown commands are appended to, one per line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dump_unknown: Option<String>,
+    // Base url of the live api which the danmu client requests, e.g. a local mock server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base: Option<String>,
+}
+
+// Base url of the live api of BiliBili.
+pub const DEFAULT_API_BASE: &str = "https://api.live.bilibili.com";
+
+fn default_proto_ver() -> u8 {
+    3
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            credential: Credential::default(),
+            proto_ver: default_proto_ver(),
+            dump_unknown: None,
+            api_base: None,
+        }
+    }
 }
 
 impl Config {
-    pub fn from_file(path: &str) -> Result<Self, Error> {
+    pub fn from_file(path: &str) -> Result<Self> {
         let se_data = fs::read(path)?;
 
-        Ok(serde_yaml::from_slice(&se_data).unwrap())
+        Ok(serde_yaml::from_slice(&se_data)?)
+    }
+
+    pub fn api_base(&self) -> &str {
+        self.api_base.as_deref().unwrap_or(DEFAULT_API_BASE)
     }
 
-    pub fn save_as_file(&self, path: &str) -> Result<(), Error> {
-        let de_data = serde_yaml::to_string(self).unwrap();
+    pub fn save_as_file(&self, path: &str) -> Result<()> {
+        let de_data = serde_yaml::to_string(self)?;
         fs::write(path, de_data)?;
 
         Ok(())
@@ -31,6 +64,9 @@ fn test_config_serde() {
             bili_jct: "222".to_owned(),
             buvid3: "333".to_owned(),
         },
+        proto_ver: 2,
+        dump_unknown: None,
+        api_base: None,
     };
 
     let output = Config::from_file("fixtures/test_config_serde.yml").unwrap();
@@ -41,4 +77,29 @@ fn test_config_serde() {
     );
     assert_eq!(output.credential.bili_jct, expected.credential.bili_jct);
     assert_eq!(output.credential.buvid3, expected.credential.buvid3);
+    assert_eq!(output.proto_ver, expected.proto_ver);
+}
+
+#[test]
+fn test_config_default_proto_ver() {
+    let output: Config = serde_yaml::from_str(
+        "credential:\n  session_data: \"\"\n  bili_jct: \"\"\n  buvid3: \"\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(output.proto_ver, 3);
+}
+
+#[test]
+fn test_config_from_invalid_file() {
+    use crate::Error;
+
+    assert!(matches!(
+        Config::from_file("fixtures/no_such_config.yml"),
+        Err(Error::Io(_))
+    ));
+    assert!(matches!(
+        Config::from_file("fixtures/danmu_msg_medal.json"),
+        Err(Error::Decode(_))
+    ));
 }
@@ -0,0 +1,112 @@
+use std::fmt;
+
+use tokio_tungstenite::tungstenite;
+
+use crate::client::{DecodeError, AUTH_TIMEOUT};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    // The HTTP request can not be sent or its response can not be read
+    Transport(reqwest::Error),
+    // The websocket connection is broken
+    WebSocket(Box<tungstenite::Error>),
+    // The HTTP response with a non-success status
+    Status(u16),
+    // The BiliBili API response with a non-zero code
+    Api { code: i64, message: String },
+    // A response, a pack or a file can not be decoded
+    Decode(String),
+    // Reading or writing a file failed
+    Io(std::io::Error),
+    // A method, an url or an argument is invalid
+    InvalidInput(String),
+    // None of the danmu host servers can be connected
+    NoHostAvailable,
+    // The connection is closed, e.g. before the authentication response
+    Closed,
+    // The receiver of live events is dropped
+    ChannelClosed,
+    // No authentication response within AUTH_TIMEOUT
+    AuthTimeout,
+    // The authentication response can not be parsed
+    AuthInvalidResp,
+    // The authentication response with a non-zero code
+    AuthRejected(i64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "http request failed: {}", e),
+            Self::WebSocket(e) => write!(f, "websocket failed: {}", e),
+            Self::Status(status) => write!(f, "http request failed with status {}", status),
+            Self::Api { code, message } => {
+                write!(f, "api request failed (code: {}): {}", code, message)
+            }
+            Self::Decode(e) => write!(f, "decoding failed: {}", e),
+            Self::Io(e) => write!(f, "io failed: {}", e),
+            Self::InvalidInput(e) => write!(f, "invalid input: {}", e),
+            Self::NoHostAvailable => write!(f, "no danmaku host server can be connected"),
+            Self::Closed => write!(f, "connection closed"),
+            Self::ChannelClosed => write!(f, "receiver of live events is dropped"),
+            Self::AuthTimeout => write!(
+                f,
+                "no authentication response within {} seconds",
+                AUTH_TIMEOUT.as_secs()
+            ),
+            Self::AuthInvalidResp => write!(f, "invalid authentication response"),
+            Self::AuthRejected(code) => {
+                write!(f, "authentication rejected by server (code: {})", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(e) => Some(e),
+            Self::WebSocket(e) => Some(e.as_ref()),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Transport(e)
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
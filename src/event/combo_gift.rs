@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{event::medal::deserialize_medal, FanMedal};
+
+// The 'data' of COMBO_SEND, which is sent when a combo of gifts goes on.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ComboGift {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "action")]
+    pub action: String,
+    #[serde(rename = "gift_id")]
+    pub gift_id: u64,
+    #[serde(rename = "gift_name")]
+    pub gift_name: String,
+    // Number of gifts sent in this batch
+    #[serde(rename = "gift_num")]
+    pub gift_num: u32,
+    // Running number of batches in the combo
+    #[serde(rename = "combo_num")]
+    pub combo_num: u32,
+    // Running number of gifts in the combo
+    #[serde(rename = "total_num")]
+    pub total_num: u32,
+    // Running gold coins of the combo, 1000 gold coins = 1 CNY
+    #[serde(rename = "combo_total_coin")]
+    pub combo_total_coin: u64,
+    #[serde(rename = "medal_info", deserialize_with = "deserialize_medal")]
+    pub medal: Option<FanMedal>,
+}
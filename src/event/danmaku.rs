@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{utils, FanMedal};
+
+// The 'info' of DANMU_MSG, which is a positional array:
+//
+// info[0]: [_, mode, font_size, color, timestamp_ms, _, _, _, _, _, _, _, dm_type, emoticon, ...]
+// info[1]: content
+// info[2]: [uid, uname, is_admin, ...]
+// info[3]: [medal_level, medal_name, anchor_uname, anchor_roomid, ...] or []
+// info[4]: [user_level, ...]
+// info[7]: guard_level
+// info[9]: {"ts": timestamp, ...}
+//
+// Missing or null entries fall back to their default values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Danmaku {
+    pub uid: u64,
+    pub uname: String,
+    pub content: String,
+    pub medal: Option<FanMedal>,
+    pub is_admin: bool,
+    pub user_level: u32,
+    pub guard_level: u8, /* 0: none, 1: governor, 2: admiral, 3: captain */
+    pub color: u32,      /* RGB, e.g. 0xffffff */
+    pub mode: u8,        /* 1: scroll, 4: bottom, 5: top */
+    pub font_size: u32,
+    pub emoticon: Option<Emoticon>,
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
+
+// The emoticon sent instead of text, the content is the name of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Emoticon {
+    #[serde(rename = "emoticon_unique")]
+    pub unique: String,
+    #[serde(rename = "url")]
+    pub url: String,
+    #[serde(rename = "width")]
+    pub width: u32,
+    #[serde(rename = "height")]
+    pub height: u32,
+}
+
+impl<'de> Deserialize<'de> for Danmaku {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let info = Vec::<Value>::deserialize(deserializer)?;
+        let at = |i: usize| info.get(i).unwrap_or(&Value::Null);
+        let as_u64 = |v: &Value| v.as_u64().unwrap_or_default();
+        let as_string = |v: &Value| v.as_str().unwrap_or_default().to_owned();
+
+        let (meta, user, medal) = (at(0), at(2), at(3));
+        let medal = match medal[1].as_str() {
+            Some(name) if !name.is_empty() => Some(FanMedal {
+                name: name.to_owned(),
+                level: as_u64(&medal[0]) as u32,
+                anchor_uname: as_string(&medal[2]),
+                anchor_room_id: as_u64(&medal[3]),
+            }),
+            _ => None,
+        };
+        // The emoticon is only meaningful when 'dm_type' is 1.
+        let emoticon = match as_u64(&meta[12]) {
+            1 => Emoticon::deserialize(&meta[13]).ok(),
+            _ => None,
+        };
+        let timestamp = match at(9)["ts"].as_u64() {
+            Some(ts) => ts,
+            None => as_u64(&meta[4]) / 1000,
+        };
+
+        Ok(Self {
+            uid: as_u64(&user[0]),
+            uname: as_string(&user[1]),
+            content: as_string(at(1)),
+            medal,
+            is_admin: as_u64(&user[2]) == 1,
+            user_level: as_u64(&at(4)[0]) as u32,
+            guard_level: as_u64(at(7)) as u8,
+            color: as_u64(&meta[3]) as u32,
+            mode: as_u64(&meta[1]) as u8,
+            font_size: as_u64(&meta[2]) as u32,
+            emoticon,
+            timestamp,
+        })
+    }
+}
+
+impl Danmaku {
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::timestamp_to_datetime_utc8(self.timestamp)
+    }
+}
+
+#[cfg(test)]
+fn danmaku_from_fixture(path: &str) -> Danmaku {
+    let json: Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    Danmaku::deserialize(&json["info"]).unwrap()
+}
+
+#[test]
+fn test_danmaku_serde_medal() {
+    let danmaku = danmaku_from_fixture("fixtures/danmu_msg_medal.json");
+
+    assert_eq!(danmaku.uid, 12345678);
+    assert_eq!(danmaku.uname, "测试用户");
+    assert_eq!(danmaku.content, "晚上好");
+    assert_eq!(
+        danmaku.medal,
+        Some(FanMedal {
+            name: "魔法棒".to_owned(),
+            level: 21,
+            anchor_uname: "魔法Zc目录".to_owned(),
+            anchor_room_id: 3044248,
+        })
+    );
+    assert!(danmaku.is_admin);
+    assert_eq!(danmaku.user_level, 23);
+    assert_eq!(danmaku.guard_level, 3);
+    assert_eq!(danmaku.color, 0xffffff);
+    assert_eq!(danmaku.mode, 1);
+    assert_eq!(danmaku.font_size, 25);
+    assert_eq!(danmaku.emoticon, None);
+    assert_eq!(danmaku.timestamp, 1674026138);
+}
+
+#[test]
+fn test_danmaku_serde_emoticon() {
+    let danmaku = danmaku_from_fixture("fixtures/danmu_msg_emoticon.json");
+
+    assert_eq!(danmaku.uid, 87654321);
+    assert_eq!(danmaku.content, "干杯");
+    assert_eq!(danmaku.medal, None);
+    assert!(!danmaku.is_admin);
+    assert_eq!(danmaku.guard_level, 0);
+    let emoticon = danmaku.emoticon.unwrap();
+    assert_eq!(emoticon.unique, "official_147");
+    assert_eq!((emoticon.width, emoticon.height), (150, 60));
+}
+
+#[test]
+fn test_danmaku_serde_truncated() {
+    let danmaku = danmaku_from_fixture("fixtures/danmu_msg_truncated.json");
+
+    assert_eq!(danmaku.uid, 10001);
+    assert_eq!(danmaku.uname, "旧版用户");
+    assert_eq!(danmaku.content, "弹幕");
+    assert_eq!(danmaku.medal, None);
+    assert_eq!(danmaku.user_level, 0);
+    assert_eq!(danmaku.mode, 4);
+    assert_eq!(danmaku.color, 14893055);
+    // fall back to the timestamp in milliseconds of info[0]
+    assert_eq!(danmaku.timestamp, 1674026512);
+}
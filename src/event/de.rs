@@ -0,0 +1,15 @@
+use serde::{de, Deserialize, Deserializer};
+
+// Some fields are sent as a number by a command but as a string by another,
+// e.g. 'roomid' of PREPARING and 'score' of ONLINE_RANK_V2.
+pub(crate) fn deserialize_u64_lenient<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n.as_u64().ok_or_else(|| de::Error::custom("not a u64")),
+        serde_json::Value::String(s) if s.is_empty() => Ok(0),
+        serde_json::Value::String(s) => s.parse().map_err(de::Error::custom),
+        _ => Ok(0),
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{event::medal::deserialize_medal, utils, FanMedal};
+
+// The 'data' of SEND_GIFT.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Gift {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "action")]
+    pub action: String,
+    #[serde(rename = "giftId")]
+    pub gift_id: u64,
+    #[serde(rename = "giftName")]
+    pub gift_name: String,
+    #[serde(rename = "num")]
+    pub num: u32,
+    // Price of a single gift, 1000 gold coins = 1 CNY
+    #[serde(rename = "price")]
+    pub price: u64,
+    // "gold" or "silver"
+    #[serde(rename = "coin_type")]
+    pub coin_type: String,
+    #[serde(rename = "total_coin")]
+    pub total_coin: u64,
+    #[serde(rename = "medal_info", deserialize_with = "deserialize_medal")]
+    pub medal: Option<FanMedal>,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
+
+impl Gift {
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::timestamp_to_datetime_utc8(self.timestamp)
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
+
+// The 'data' of GUARD_BUY and USER_TOAST_MSG, both are sent for the same purchase.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GuardBuy {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "username")]
+    pub username: String,
+    // 1: governor, 2: admiral, 3: captain
+    #[serde(rename = "guard_level")]
+    pub guard_level: u8,
+    #[serde(rename = "num")]
+    pub num: u32,
+    // "月" (month) or "周" (week), only USER_TOAST_MSG carries it
+    #[serde(rename = "unit")]
+    pub unit: String,
+    // Price of a single unit, 1000 gold coins = 1 CNY
+    #[serde(rename = "price")]
+    pub price: u64,
+    #[serde(rename = "gift_name", alias = "role_name")]
+    pub gift_name: String,
+    #[serde(rename = "start_time")]
+    pub start_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "end_time")]
+    pub end_time: u64, /* Unix timestamp in seconds */
+}
+
+impl GuardBuy {
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::timestamp_to_datetime_utc8(self.start_time)
+    }
+
+    // Months purchased, GUARD_BUY does not carry the unit and is always by month.
+    pub fn months(&self) -> u32 {
+        match self.unit.as_str() {
+            "" | "月" => self.num,
+            _ => 0,
+        }
+    }
+
+    pub fn guard_name(&self) -> &'static str {
+        match self.guard_level {
+            1 => "Governor",
+            2 => "Admiral",
+            3 => "Captain",
+            _ => "Guard",
+        }
+    }
+
+    // GUARD_BUY and USER_TOAST_MSG of the same purchase are equal in these fields.
+    pub fn is_same_purchase(&self, other: &GuardBuy) -> bool {
+        self.uid == other.uid
+            && self.guard_level == other.guard_level
+            && self.start_time == other.start_time
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+// The 'data' of HOT_RANK_CHANGED_V2, the room moves in the hot rank of its area.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HotRankChanged {
+    // 0 if the room is not in the rank
+    #[serde(rename = "rank")]
+    pub rank: u32,
+    #[serde(rename = "area_name")]
+    pub area_name: String,
+    // e.g. "限时热门榜"
+    #[serde(rename = "rank_desc")]
+    pub rank_desc: String,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
+
+// The 'data' of AREA_RANK_CHANGED, the room moves in the rank of its area.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AreaRankChanged {
+    #[serde(rename = "rank")]
+    pub rank: u32,
+    // e.g. "人气榜"
+    #[serde(rename = "rank_name")]
+    pub rank_name: String,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
+
+// The 'data' of HOT_RANK_SETTLEMENT, the final hot rank of the last period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HotRankSettlement {
+    #[serde(rename = "rank")]
+    pub rank: u32,
+    #[serde(rename = "area_name")]
+    pub area_name: String,
+    // e.g. "恭喜主播 <% xxx %> 荣登限时热门榜单主播总榜top10!"
+    #[serde(rename = "dm_msg")]
+    pub dm_msg: String,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{event::medal::deserialize_medal, utils, FanMedal};
+
+// The 'data' of INTERACT_WORD.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Interact {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "msg_type")]
+    pub kind: InteractKind,
+    #[serde(rename = "fans_medal", deserialize_with = "deserialize_medal")]
+    pub medal: Option<FanMedal>,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64, /* Unix timestamp in seconds */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum InteractKind {
+    Enter,         /* 1 */
+    Follow,        /* 2 */
+    Share,         /* 3 */
+    SpecialFollow, /* 4 */
+    MutualFollow,  /* 5 */
+    Like,          /* 6 */
+    Unknown(u8),
+}
+
+impl Default for InteractKind {
+    fn default() -> Self {
+        Self::Unknown(0)
+    }
+}
+
+impl From<u8> for InteractKind {
+    fn from(msg_type: u8) -> Self {
+        match msg_type {
+            1 => Self::Enter,
+            2 => Self::Follow,
+            3 => Self::Share,
+            4 => Self::SpecialFollow,
+            5 => Self::MutualFollow,
+            6 => Self::Like,
+            _ => Self::Unknown(msg_type),
+        }
+    }
+}
+
+impl From<InteractKind> for u8 {
+    fn from(kind: InteractKind) -> Self {
+        match kind {
+            InteractKind::Enter => 1,
+            InteractKind::Follow => 2,
+            InteractKind::Share => 3,
+            InteractKind::SpecialFollow => 4,
+            InteractKind::MutualFollow => 5,
+            InteractKind::Like => 6,
+            InteractKind::Unknown(msg_type) => msg_type,
+        }
+    }
+}
+
+impl Interact {
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::timestamp_to_datetime_utc8(self.timestamp)
+    }
+}
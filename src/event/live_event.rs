@@ -0,0 +1,321 @@
+use serde::Deserialize;
+
+use crate::{
+    AnchorLot, AnchorLotAward, AreaRankChanged, ComboGift, CutOff, Danmaku, Gift, GuardBuy,
+    HotRankChanged, HotRankSettlement, Interact, LikeInfo, LiveStart, Notice, OnlineRank,
+    OnlineRankCount, PkBattlePre, PkBattleScore, PkBattleSettle, PkBattleStart, Preparing,
+    RedPocket, RedPocketWinners, RoomBlock, RoomChange, RoomSilent, SuperChat, SuperChatDelete,
+    Warning, WatchedChange,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveEvent {
+    Danmaku(Danmaku),                     /* DANMU_MSG */
+    Gift(Gift),                           /* SEND_GIFT */
+    SuperChat(SuperChat),                 /* SUPER_CHAT_MESSAGE */
+    SuperChatJpn(SuperChat),              /* SUPER_CHAT_MESSAGE_JPN */
+    SuperChatDelete(SuperChatDelete),     /* SUPER_CHAT_MESSAGE_DELETE */
+    ComboGift(ComboGift),                 /* COMBO_SEND */
+    Interact(Interact),                   /* INTERACT_WORD */
+    Notice(Notice),                       /* NOTICE_MSG */
+    GuardBuy(GuardBuy),                   /* GUARD_BUY, USER_TOAST_MSG */
+    LikeInfo(LikeInfo),                   /* LIKE_INFO_V3_UPDATE */
+    WatchedChange(WatchedChange),         /* WATCHED_CHANGE */
+    OnlineRankCount(OnlineRankCount),     /* ONLINE_RANK_COUNT */
+    OnlineRank(OnlineRank),               /* ONLINE_RANK_V2 */
+    Live(LiveStart),                      /* LIVE */
+    Preparing(Preparing),                 /* PREPARING */
+    RoomChange(RoomChange),               /* ROOM_CHANGE */
+    RoomBlock(RoomBlock),                 /* ROOM_BLOCK_MSG */
+    RoomSilentOn(RoomSilent),             /* ROOM_SILENT_ON */
+    RoomSilentOff(RoomSilent),            /* ROOM_SILENT_OFF */
+    Warning(Warning),                     /* WARNING */
+    CutOff(CutOff),                       /* CUT_OFF */
+    AnchorLotStart(AnchorLot),            /* ANCHOR_LOT_START */
+    AnchorLotAward(AnchorLotAward),       /* ANCHOR_LOT_AWARD */
+    RedPocketStart(RedPocket),            /* POPULARITY_RED_POCKET_START */
+    RedPocketWinners(RedPocketWinners),   /* POPULARITY_RED_POCKET_WINNER_LIST */
+    PkBattlePre(PkBattlePre),             /* PK_BATTLE_PRE */
+    PkBattleStart(PkBattleStart),         /* PK_BATTLE_START */
+    PkBattleProcess(PkBattleScore),       /* PK_BATTLE_PROCESS */
+    PkBattleEnd(PkBattleScore),           /* PK_BATTLE_END */
+    PkBattleSettle(PkBattleSettle),       /* PK_BATTLE_SETTLE */
+    HotRankChanged(HotRankChanged),       /* HOT_RANK_CHANGED_V2 */
+    AreaRankChanged(AreaRankChanged),     /* AREA_RANK_CHANGED */
+    HotRankSettlement(HotRankSettlement), /* HOT_RANK_SETTLEMENT */
+    // Not a command, but the popularity carried by the heart beat response pack.
+    Popularity(u32),
+    // Not a command, the connection is lost and the client is reconnecting.
+    Reconnecting(Reconnecting),
+    // Not a command, the client is connected again after reconnecting.
+    Reconnected,
+    // A command which is unknown or can not be parsed.
+    Unknown(RawCommand),
+}
+
+// A live event tagged with the room it comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomEvent {
+    pub room_id: u32,
+    pub event: LiveEvent,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawCommand {
+    // The name of the command, INVALID_JSON_CMD if the body is not JSON,
+    // or MALFORMED_PACK_CMD if the pack can not be decoded
+    pub cmd: String,
+    pub raw: String,
+}
+
+// The status of reconnecting, emitted before every attempt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reconnecting {
+    pub retries: u32,   /* the failed attempts so far */
+    pub reason: String, /* why the connection or the last attempt failed */
+}
+
+// The name of the RawCommand whose body is not JSON.
+pub const INVALID_JSON_CMD: &str = "<invalid json>";
+
+// The name of the RawCommand for a malformed pack, whose raw is the decoding error.
+pub const MALFORMED_PACK_CMD: &str = "<malformed pack>";
+
+impl LiveEvent {
+    // Parse the body of the normal pack.
+    // If the command is unknown or malformed, return LiveEvent::Unknown with the raw body.
+    pub fn from_body(body: &[u8]) -> Self {
+        let raw = || String::from_utf8_lossy(body).into_owned();
+        let json: serde_json::Value = match serde_json::from_slice(body) {
+            Ok(json) => json,
+            Err(_) => {
+                return Self::Unknown(RawCommand {
+                    cmd: INVALID_JSON_CMD.to_owned(),
+                    raw: raw(),
+                })
+            }
+        };
+
+        match Self::from_command(&json) {
+            Some(event) => event,
+            None => Self::Unknown(RawCommand {
+                cmd: json["cmd"].as_str().unwrap_or_default().to_owned(),
+                raw: raw(),
+            }),
+        }
+    }
+
+    // Parse a command from the normal pack.
+    // If the command is unknown or malformed, return None.
+    pub fn from_command(json: &serde_json::Value) -> Option<Self> {
+        // Some commands carry suffixes, e.g. "DANMU_MSG:4:0:2:2:2:0".
+        let cmd = json["cmd"].as_str()?.split(':').next()?;
+        let data = &json["data"];
+        match cmd {
+            "DANMU_MSG" => Danmaku::deserialize(&json["info"]).ok().map(Self::Danmaku),
+            "SEND_GIFT" => Gift::deserialize(data).ok().map(Self::Gift),
+            "SUPER_CHAT_MESSAGE" => SuperChat::deserialize(data).ok().map(Self::SuperChat),
+            "SUPER_CHAT_MESSAGE_JPN" => SuperChat::deserialize(data).ok().map(Self::SuperChatJpn),
+            "SUPER_CHAT_MESSAGE_DELETE" => SuperChatDelete::deserialize(data)
+                .ok()
+                .map(Self::SuperChatDelete),
+            "COMBO_SEND" => ComboGift::deserialize(data).ok().map(Self::ComboGift),
+            "INTERACT_WORD" => Interact::deserialize(data).ok().map(Self::Interact),
+            "NOTICE_MSG" => Notice::deserialize(json).ok().map(Self::Notice),
+            "GUARD_BUY" | "USER_TOAST_MSG" => GuardBuy::deserialize(data).ok().map(Self::GuardBuy),
+            "LIKE_INFO_V3_UPDATE" => LikeInfo::deserialize(data).ok().map(Self::LikeInfo),
+            "WATCHED_CHANGE" => WatchedChange::deserialize(data)
+                .ok()
+                .map(Self::WatchedChange),
+            "ONLINE_RANK_COUNT" => OnlineRankCount::deserialize(data)
+                .ok()
+                .map(Self::OnlineRankCount),
+            "ONLINE_RANK_V2" => OnlineRank::deserialize(data).ok().map(Self::OnlineRank),
+            "LIVE" => LiveStart::deserialize(json).ok().map(Self::Live),
+            "PREPARING" => Preparing::deserialize(json).ok().map(Self::Preparing),
+            "ROOM_CHANGE" => RoomChange::deserialize(data).ok().map(Self::RoomChange),
+            "ROOM_BLOCK_MSG" => RoomBlock::deserialize(data).ok().map(Self::RoomBlock),
+            // ROOM_SILENT_OFF may come without 'data'
+            "ROOM_SILENT_ON" => RoomSilent::deserialize(data).ok().map(Self::RoomSilentOn),
+            "ROOM_SILENT_OFF" => Some(Self::RoomSilentOff(
+                RoomSilent::deserialize(data).unwrap_or_default(),
+            )),
+            "WARNING" => Warning::deserialize(json).ok().map(Self::Warning),
+            "CUT_OFF" => CutOff::deserialize(json).ok().map(Self::CutOff),
+            "ANCHOR_LOT_START" => AnchorLot::deserialize(data).ok().map(Self::AnchorLotStart),
+            "ANCHOR_LOT_AWARD" => AnchorLotAward::deserialize(data)
+                .ok()
+                .map(Self::AnchorLotAward),
+            "POPULARITY_RED_POCKET_START" => {
+                RedPocket::deserialize(data).ok().map(Self::RedPocketStart)
+            }
+            "POPULARITY_RED_POCKET_WINNER_LIST" => RedPocketWinners::deserialize(data)
+                .ok()
+                .map(Self::RedPocketWinners),
+            // The '_NEW' ones are sent along with the old ones in the same format.
+            "PK_BATTLE_PRE" | "PK_BATTLE_PRE_NEW" => {
+                PkBattlePre::deserialize(data).ok().map(Self::PkBattlePre)
+            }
+            "PK_BATTLE_START" | "PK_BATTLE_START_NEW" => PkBattleStart::deserialize(data)
+                .ok()
+                .map(Self::PkBattleStart),
+            "PK_BATTLE_PROCESS" | "PK_BATTLE_PROCESS_NEW" => PkBattleScore::deserialize(data)
+                .ok()
+                .map(Self::PkBattleProcess),
+            "PK_BATTLE_END" => PkBattleScore::deserialize(data).ok().map(Self::PkBattleEnd),
+            "PK_BATTLE_SETTLE" => PkBattleSettle::deserialize(data)
+                .ok()
+                .map(Self::PkBattleSettle),
+            "HOT_RANK_CHANGED_V2" => HotRankChanged::deserialize(data)
+                .ok()
+                .map(Self::HotRankChanged),
+            "AREA_RANK_CHANGED" => AreaRankChanged::deserialize(data)
+                .ok()
+                .map(Self::AreaRankChanged),
+            "HOT_RANK_SETTLEMENT" | "HOT_RANK_SETTLEMENT_V2" => {
+                HotRankSettlement::deserialize(data)
+                    .ok()
+                    .map(Self::HotRankSettlement)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_live_event_from_command() {
+    let json = serde_json::json!({
+        "cmd": "SEND_GIFT",
+        "data": {
+            "uid": 10086,
+            "uname": "user",
+            "action": "投喂",
+            "giftId": 31036,
+            "giftName": "小花花",
+            "num": 3,
+            "price": 100,
+            "coin_type": "gold",
+            "total_coin": 300,
+            "timestamp": 1674000000,
+            "medal_info": { "medal_name": "", "medal_level": 0 }
+        }
+    });
+    match LiveEvent::from_command(&json) {
+        Some(LiveEvent::Gift(gift)) => {
+            assert_eq!(gift.uid, 10086);
+            assert_eq!(gift.gift_name, "小花花");
+            assert_eq!(gift.num, 3);
+            assert_eq!(gift.total_coin, 300);
+            assert_eq!(gift.medal, None);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let json = serde_json::json!({
+        "cmd": "SUPER_CHAT_MESSAGE",
+        "data": {
+            "id": 1,
+            "uid": 10086,
+            "user_info": { "uname": "user", "guard_level": 3 },
+            "message": "hello",
+            "price": 30,
+            "time": 60,
+            "start_time": 1674000000,
+            "end_time": 1674000060,
+            "medal_info": { "medal_name": "medal", "medal_level": 21, "anchor_roomid": 3 }
+        }
+    });
+    match LiveEvent::from_command(&json) {
+        Some(LiveEvent::SuperChat(sc)) => {
+            assert_eq!(sc.user_info.uname, "user");
+            assert_eq!(sc.price, 30);
+            assert_eq!(sc.medal.unwrap().level, 21);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let json = serde_json::json!({
+        "cmd": "INTERACT_WORD",
+        "data": {
+            "uid": 10086,
+            "uname": "user",
+            "msg_type": 2,
+            "timestamp": 1674000000,
+            "fans_medal": { "medal_name": "medal", "medal_level": 5, "anchor_roomid": 3 }
+        }
+    });
+    match LiveEvent::from_command(&json) {
+        Some(LiveEvent::Interact(interact)) => {
+            assert_eq!(interact.kind, crate::InteractKind::Follow);
+            assert_eq!(interact.medal.unwrap().anchor_room_id, 3);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let json = serde_json::json!({
+        "cmd": "USER_TOAST_MSG",
+        "data": {
+            "uid": 10086,
+            "username": "user",
+            "guard_level": 3,
+            "num": 1,
+            "unit": "月",
+            "price": 198000,
+            "role_name": "舰长",
+            "start_time": 1674000000,
+            "end_time": 1674000000
+        }
+    });
+    match LiveEvent::from_command(&json) {
+        Some(LiveEvent::GuardBuy(guard)) => {
+            assert_eq!(guard.gift_name, "舰长");
+            assert_eq!(guard.months(), 1);
+            assert_eq!(guard.price, 198000);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let json = serde_json::json!({
+        "cmd": "HOT_RANK_CHANGED_V2",
+        "data": { "rank": 7, "trend": 1, "area_name": "虚拟主播", "rank_desc": "限时热门榜" }
+    });
+    match LiveEvent::from_command(&json) {
+        Some(LiveEvent::HotRankChanged(hot_rank)) => {
+            assert_eq!(hot_rank.rank, 7);
+            assert_eq!(hot_rank.area_name, "虚拟主播");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let json = serde_json::json!({ "cmd": "ROOM_SILENT_OFF" });
+    assert_eq!(
+        LiveEvent::from_command(&json),
+        Some(LiveEvent::RoomSilentOff(Default::default()))
+    );
+
+    let json = serde_json::json!({ "cmd": "UNKNOWN_CMD", "data": {} });
+    assert_eq!(LiveEvent::from_command(&json), None);
+}
+
+#[test]
+fn test_live_event_from_body() {
+    let body = br#"{"cmd":"NEW_CMD_FROM_FUTURE","data":{"a":1}}"#;
+    assert_eq!(
+        LiveEvent::from_body(body),
+        LiveEvent::Unknown(RawCommand {
+            cmd: "NEW_CMD_FROM_FUTURE".to_owned(),
+            raw: String::from_utf8(body.to_vec()).unwrap(),
+        })
+    );
+
+    match LiveEvent::from_body(b"{\"cmd\":") {
+        LiveEvent::Unknown(raw) => assert_eq!(raw.cmd, INVALID_JSON_CMD),
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let body = br#"{"cmd":"PREPARING","roomid":"3044248"}"#;
+    assert!(matches!(
+        LiveEvent::from_body(body),
+        LiveEvent::Preparing(_)
+    ));
+}
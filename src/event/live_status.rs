@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::event::de::deserialize_u64_lenient;
+
+// LIVE, the streamer goes live. Unlike most commands, its fields are not under 'data'.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LiveStart {
+    #[serde(rename = "roomid", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+    // Unix timestamp in seconds, older servers do not send it
+    #[serde(rename = "live_time")]
+    pub live_time: u64,
+    #[serde(rename = "live_platform")]
+    pub live_platform: String,
+}
+
+// PREPARING, the streamer ends the live. Its fields are not under 'data' either.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Preparing {
+    #[serde(rename = "roomid", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+}
+
+// The 'data' of ROOM_CHANGE, the streamer changes the title or the area.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RoomChange {
+    #[serde(rename = "title")]
+    pub title: String,
+    #[serde(rename = "area_id")]
+    pub area_id: u64,
+    #[serde(rename = "area_name")]
+    pub area_name: String,
+    #[serde(rename = "parent_area_id")]
+    pub parent_area_id: u64,
+    #[serde(rename = "parent_area_name")]
+    pub parent_area_name: String,
+}
+
+#[test]
+fn test_live_status_serde() {
+    let json = serde_json::json!({ "cmd": "LIVE", "roomid": 3044248, "live_time": 1674000000 });
+    let live = LiveStart::deserialize(&json).unwrap();
+    assert_eq!((live.room_id, live.live_time), (3044248, 1674000000));
+
+    let json = serde_json::json!({ "cmd": "PREPARING", "roomid": "3044248" });
+    assert_eq!(Preparing::deserialize(&json).unwrap().room_id, 3044248);
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::event::de::deserialize_u64_lenient;
+
+// The 'data' of ANCHOR_LOT_START, the streamer starts a lottery (天选时刻).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnchorLot {
+    #[serde(rename = "id")]
+    pub id: u64,
+    #[serde(rename = "award_name")]
+    pub award_name: String,
+    #[serde(rename = "award_num")]
+    pub award_num: u32,
+    // The danmaku to send to join the lottery
+    #[serde(rename = "danmu")]
+    pub danmu: String,
+    // Other requirements, e.g. "至少成为主播的粉丝"
+    #[serde(rename = "require_text")]
+    pub require_text: String,
+    // Seconds left until the lottery is drawn
+    #[serde(rename = "time")]
+    pub time: u64,
+    #[serde(rename = "current_time")]
+    pub current_time: u64, /* Unix timestamp in seconds */
+}
+
+impl AnchorLot {
+    pub fn end_time(&self) -> u64 {
+        self.current_time + self.time
+    }
+}
+
+// The 'data' of ANCHOR_LOT_AWARD, the lottery is drawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnchorLotAward {
+    #[serde(rename = "id")]
+    pub id: u64,
+    #[serde(rename = "award_name")]
+    pub award_name: String,
+    #[serde(rename = "award_num")]
+    pub award_num: u32,
+    #[serde(rename = "award_users")]
+    pub award_users: Vec<LotteryWinner>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LotteryWinner {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+}
+
+// The 'data' of POPULARITY_RED_POCKET_START, someone sends a red envelope.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedPocket {
+    #[serde(rename = "lot_id")]
+    pub lot_id: u64,
+    #[serde(rename = "sender_uid")]
+    pub sender_uid: u64,
+    #[serde(rename = "sender_name")]
+    pub sender_name: String,
+    // The danmaku to send to join the red envelope
+    #[serde(rename = "danmu")]
+    pub danmu: String,
+    // Price in gold seeds (1000 = 1 CNY)
+    #[serde(rename = "total_price")]
+    pub total_price: u64,
+    #[serde(rename = "start_time")]
+    pub start_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "end_time")]
+    pub end_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "awards")]
+    pub awards: Vec<RedPocketAward>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedPocketAward {
+    #[serde(rename = "gift_id")]
+    pub gift_id: u64,
+    #[serde(rename = "gift_name")]
+    pub gift_name: String,
+    #[serde(rename = "num")]
+    pub num: u32,
+}
+
+impl RedPocket {
+    // The awards in a line, e.g. "小花花x10, 打call x2"
+    pub fn awards_text(&self) -> String {
+        self.awards
+            .iter()
+            .map(|v| format!("{}x{}", v.gift_name, v.num))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// The 'data' of POPULARITY_RED_POCKET_WINNER_LIST, the red envelope is drawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedPocketWinners {
+    #[serde(rename = "lot_id")]
+    pub lot_id: u64,
+    #[serde(rename = "total_num")]
+    pub total_num: u32,
+    #[serde(rename = "winner_info", deserialize_with = "deserialize_winner_info")]
+    pub winners: Vec<LotteryWinner>,
+}
+
+// Every winner is a positional array: [uid, uname, _, award_gift_id, ...]
+fn deserialize_winner_info<'de, D>(deserializer: D) -> Result<Vec<LotteryWinner>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let winners = Vec::<Vec<Value>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|info| LotteryWinner {
+            uid: deserialize_u64_lenient(info.first().unwrap_or(&Value::Null)).unwrap_or_default(),
+            uname: info
+                .get(1)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_owned(),
+        })
+        .collect();
+
+    Ok(winners)
+}
+
+#[test]
+fn test_lottery_serde() {
+    let json = serde_json::json!({
+        "id": 1,
+        "award_name": "舰长",
+        "award_num": 2,
+        "danmu": "冲冲冲",
+        "time": 600,
+        "current_time": 1674000000
+    });
+    let lot = AnchorLot::deserialize(&json).unwrap();
+    assert_eq!(lot.end_time(), 1674000600);
+
+    let json = serde_json::json!({
+        "lot_id": 7,
+        "total_num": 2,
+        "winner_info": [[10086, "user", 5720208, 31212], ["10010", "other", 5720209, 31213]]
+    });
+    let winners = RedPocketWinners::deserialize(&json).unwrap();
+    assert_eq!(winners.winners.len(), 2);
+    assert_eq!(winners.winners[1].uid, 10010);
+    assert_eq!(winners.winners[1].uname, "other");
+}
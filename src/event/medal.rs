@@ -0,0 +1,24 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FanMedal {
+    #[serde(rename = "medal_name")]
+    pub name: String,
+    #[serde(rename = "medal_level")]
+    pub level: u32,
+    #[serde(rename = "anchor_uname")]
+    pub anchor_uname: String,
+    #[serde(rename = "anchor_roomid")]
+    pub anchor_room_id: u64,
+}
+
+// The server sends an empty medal (no name and level 0) if the user does not wear one.
+pub(crate) fn deserialize_medal<'de, D>(deserializer: D) -> Result<Option<FanMedal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let medal = Option::<FanMedal>::deserialize(deserializer)?;
+
+    Ok(medal.filter(|m| !m.name.is_empty()))
+}
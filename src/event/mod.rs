@@ -0,0 +1,32 @@
+mod combo_gift;
+mod danmaku;
+mod de;
+mod gift;
+mod guard_buy;
+mod hot_rank;
+mod interact;
+mod live_event;
+mod live_status;
+mod lottery;
+mod medal;
+mod moderation;
+mod notice;
+mod pk_battle;
+mod room_stats;
+mod super_chat;
+
+pub use combo_gift::*;
+pub use danmaku::*;
+pub use gift::*;
+pub use guard_buy::*;
+pub use hot_rank::*;
+pub use interact::*;
+pub use live_event::*;
+pub use live_status::*;
+pub use lottery::*;
+pub use medal::*;
+pub use moderation::*;
+pub use notice::*;
+pub use pk_battle::*;
+pub use room_stats::*;
+pub use super_chat::*;
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::event::de::deserialize_u64_lenient;
+
+// The 'data' of ROOM_BLOCK_MSG, a user is muted in the room.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RoomBlock {
+    #[serde(rename = "uid", deserialize_with = "deserialize_u64_lenient")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "operator")]
+    pub operator: u8, /* 1: room admin, 2: streamer */
+}
+
+impl RoomBlock {
+    pub fn operator_name(&self) -> &'static str {
+        match self.operator {
+            1 => "房管",
+            2 => "主播",
+            _ => "管理员",
+        }
+    }
+}
+
+// The 'data' of ROOM_SILENT_ON and ROOM_SILENT_OFF, the whole room is muted or unmuted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RoomSilent {
+    // Who is muted: "level" (below a user level), "medal" (below a medal level) or "member" (everyone)
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "level")]
+    pub level: u32,
+    // Unix timestamp in seconds when the silence ends, -1 if it lasts until turned off
+    #[serde(rename = "second")]
+    pub second: i64,
+}
+
+impl RoomSilent {
+    pub fn description(&self) -> String {
+        match self.kind.as_str() {
+            "level" => format!("用户等级 {} 以下禁言", self.level),
+            "medal" => format!("粉丝勋章等级 {} 以下禁言", self.level),
+            _ => "全员禁言".to_owned(),
+        }
+    }
+}
+
+// WARNING, the super admin warns the streamer. Its fields are not under 'data'.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Warning {
+    #[serde(rename = "msg")]
+    pub msg: String,
+    #[serde(rename = "roomid", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+}
+
+// CUT_OFF, the stream is cut by the platform. Its fields are not under 'data'.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CutOff {
+    #[serde(rename = "msg")]
+    pub msg: String,
+    #[serde(rename = "roomid", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+}
+
+#[test]
+fn test_moderation_serde() {
+    let json = serde_json::json!({ "uid": "10086", "uname": "user", "operator": 2, "dmscore": 30 });
+    let block = RoomBlock::deserialize(&json).unwrap();
+    assert_eq!((block.uid, block.operator_name()), (10086, "主播"));
+
+    let json = serde_json::json!({ "type": "medal", "level": 5, "second": -1 });
+    let silent = RoomSilent::deserialize(&json).unwrap();
+    assert_eq!(silent.second, -1);
+    assert_eq!(silent.description(), "粉丝勋章等级 5 以下禁言");
+
+    let json = serde_json::json!({ "cmd": "CUT_OFF", "msg": "违反直播规范", "roomid": 3044248 });
+    let cut_off = CutOff::deserialize(&json).unwrap();
+    assert_eq!(
+        (cut_off.msg.as_str(), cut_off.room_id),
+        ("违反直播规范", 3044248)
+    );
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+// NOTICE_MSG, the system broadcast. Unlike other commands, its fields are not under 'data'.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Notice {
+    #[serde(rename = "id")]
+    pub id: u64,
+    #[serde(rename = "name")]
+    pub name: String,
+    // The message with markups, e.g. "<%user%> sent a gift to <%anchor%>"
+    #[serde(rename = "msg_common")]
+    pub msg_common: String,
+    #[serde(rename = "msg_type")]
+    pub msg_type: u32,
+    #[serde(rename = "real_roomid")]
+    pub real_room_id: u64,
+    #[serde(rename = "link_url")]
+    pub link_url: String,
+}
+
+impl Notice {
+    // The message without markups.
+    pub fn text(&self) -> String {
+        self.msg_common.replace("<%", "").replace("%>", "")
+    }
+}
+
+#[test]
+fn test_notice_text() {
+    let notice = Notice {
+        msg_common: "<%user%> 投喂:<%anchor%>1个小电视飞船".to_owned(),
+        ..Default::default()
+    };
+
+    assert_eq!(notice.text(), "user 投喂:anchor1个小电视飞船");
+}
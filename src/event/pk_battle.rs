@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::de::deserialize_u64_lenient;
+
+// The 'data' of PK_BATTLE_PRE, the opponent is matched and the PK is about to start.
+// It is the only PK command that carries the name of the opponent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PkBattlePre {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "room_id", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+}
+
+// The 'data' of PK_BATTLE_START.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PkBattleStart {
+    #[serde(rename = "battle_type")]
+    pub battle_type: u8,
+    #[serde(rename = "pk_start_time")]
+    pub pk_start_time: u64, /* Unix timestamp in seconds */
+    // When the scores stop counting
+    #[serde(rename = "pk_frozen_time")]
+    pub pk_frozen_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "pk_end_time")]
+    pub pk_end_time: u64, /* Unix timestamp in seconds */
+    // Name of the scores, e.g. "PK值"
+    #[serde(rename = "pk_votes_name")]
+    pub pk_votes_name: String,
+}
+
+// The 'data' of PK_BATTLE_PROCESS and PK_BATTLE_END.
+// 'init_info' is the room which started the PK, 'match_info' is the other one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PkBattleScore {
+    #[serde(rename = "init_info")]
+    pub init_info: PkSide,
+    #[serde(rename = "match_info")]
+    pub match_info: PkSide,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PkSide {
+    #[serde(rename = "room_id", deserialize_with = "deserialize_u64_lenient")]
+    pub room_id: u64,
+    #[serde(rename = "votes")]
+    pub votes: u64,
+    // The top contributor of the side
+    #[serde(rename = "best_uname")]
+    pub best_uname: String,
+    // Only in PK_BATTLE_END, see pk_result()
+    #[serde(rename = "winner_type")]
+    pub winner_type: i64,
+}
+
+// The 'data' of PK_BATTLE_SETTLE, sent after PK_BATTLE_END without the scores.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PkBattleSettle {
+    #[serde(rename = "battle_type")]
+    pub battle_type: u8,
+    // The result of the room receiving it, see pk_result()
+    #[serde(rename = "result_type")]
+    pub result_type: i64,
+    #[serde(rename = "star_light_msg")]
+    pub star_light_msg: String,
+}
+
+// Map 'winner_type' or 'result_type' to the result compared with the opponent.
+// 2: won, 1: draw, -1: lost, others (e.g. 0 if it is absent): unknown.
+pub fn pk_result(kind: i64) -> Option<Ordering> {
+    match kind {
+        2 => Some(Ordering::Greater),
+        1 => Some(Ordering::Equal),
+        -1 => Some(Ordering::Less),
+        _ => None,
+    }
+}
+
+impl PkBattleScore {
+    // Return (our side, the opponent side) from the view of 'room_id'.
+    pub fn sides(&self, room_id: u64) -> (&PkSide, &PkSide) {
+        if self.match_info.room_id == room_id {
+            (&self.match_info, &self.init_info)
+        } else {
+            (&self.init_info, &self.match_info)
+        }
+    }
+}
+
+#[test]
+fn test_pk_battle_serde() {
+    let json = serde_json::json!({
+        "battle_type": 1,
+        "init_info": { "room_id": 3044248, "votes": 120, "best_uname": "a", "winner_type": 2 },
+        "match_info": { "room_id": "21452505", "votes": 80, "best_uname": "b", "winner_type": -1 }
+    });
+    let score = PkBattleScore::deserialize(&json).unwrap();
+
+    let (ours, theirs) = score.sides(21452505);
+    assert_eq!((ours.votes, theirs.votes), (80, 120));
+    let (ours, theirs) = score.sides(3044248);
+    assert_eq!((ours.room_id, theirs.room_id), (3044248, 21452505));
+    assert_eq!(pk_result(ours.winner_type), Some(Ordering::Greater));
+
+    let json = serde_json::json!({ "battle_type": 1, "result_type": -1, "star_light_msg": "" });
+    let settle = PkBattleSettle::deserialize(&json).unwrap();
+    assert_eq!(pk_result(settle.result_type), Some(Ordering::Less));
+    assert_eq!(pk_result(PkBattleSettle::default().result_type), None);
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::event::de::deserialize_u64_lenient;
+
+// The 'data' of LIKE_INFO_V3_UPDATE.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LikeInfo {
+    // Total likes of the live room
+    #[serde(rename = "click_count")]
+    pub click_count: u64,
+}
+
+// The 'data' of WATCHED_CHANGE.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WatchedChange {
+    // Number of viewers who have watched the live
+    #[serde(rename = "num")]
+    pub num: u64,
+    // e.g. "1.2万"
+    #[serde(rename = "text_small")]
+    pub text_small: String,
+}
+
+// The 'data' of ONLINE_RANK_COUNT.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OnlineRankCount {
+    // Number of viewers in the online rank
+    #[serde(rename = "count")]
+    pub count: u64,
+    // Number of viewers online, only sent by newer servers
+    #[serde(rename = "online_count")]
+    pub online_count: u64,
+}
+
+// The 'data' of ONLINE_RANK_V2, the top viewers of the online rank.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OnlineRank {
+    #[serde(rename = "list")]
+    pub list: Vec<OnlineRankEntry>,
+    #[serde(rename = "rank_type")]
+    pub rank_type: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OnlineRankEntry {
+    #[serde(rename = "uid")]
+    pub uid: u64,
+    #[serde(rename = "uname")]
+    pub uname: String,
+    // ONLINE_RANK_V2 sends the score as a string, e.g. "1200"
+    #[serde(rename = "score", deserialize_with = "deserialize_u64_lenient")]
+    pub score: u64,
+    #[serde(rename = "rank")]
+    pub rank: u32,
+    #[serde(rename = "guard_level")]
+    pub guard_level: u8,
+}
+
+#[test]
+fn test_online_rank_serde() {
+    let json = serde_json::json!({
+        "list": [
+            { "uid": 1, "uname": "a", "score": "1200", "rank": 1, "guard_level": 3 },
+            { "uid": 2, "uname": "b", "score": 800, "rank": 2 }
+        ],
+        "rank_type": "online_rank"
+    });
+    let rank = OnlineRank::deserialize(&json).unwrap();
+
+    assert_eq!(rank.list.len(), 2);
+    assert_eq!(rank.list[0].score, 1200);
+    assert_eq!(rank.list[0].guard_level, 3);
+    assert_eq!(rank.list[1].score, 800);
+    assert_eq!(rank.list[1].guard_level, 0);
+}
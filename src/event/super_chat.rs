@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    event::{de::deserialize_u64_lenient, medal::deserialize_medal},
+    utils, FanMedal,
+};
+
+// The 'data' of SUPER_CHAT_MESSAGE and SUPER_CHAT_MESSAGE_JPN.
+// The latter carries the ids as strings and the translation in 'message_jpn'.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SuperChat {
+    #[serde(rename = "id", deserialize_with = "deserialize_u64_lenient")]
+    pub id: u64,
+    #[serde(rename = "uid", deserialize_with = "deserialize_u64_lenient")]
+    pub uid: u64,
+    #[serde(rename = "user_info")]
+    pub user_info: SuperChatUser,
+    #[serde(rename = "message")]
+    pub message: String,
+    // The Japanese translation of the message, empty if not translated
+    #[serde(rename = "message_trans", alias = "message_jpn")]
+    pub message_trans: String,
+    // Price in CNY
+    #[serde(rename = "price", deserialize_with = "deserialize_u64_lenient")]
+    pub price: u64,
+    // How long the SC is displayed, in seconds
+    #[serde(rename = "time")]
+    pub time: u64,
+    #[serde(rename = "start_time", deserialize_with = "deserialize_u64_lenient")]
+    pub start_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "end_time", deserialize_with = "deserialize_u64_lenient")]
+    pub end_time: u64, /* Unix timestamp in seconds */
+    #[serde(rename = "medal_info", deserialize_with = "deserialize_medal")]
+    pub medal: Option<FanMedal>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SuperChatUser {
+    #[serde(rename = "uname")]
+    pub uname: String,
+    #[serde(rename = "guard_level")]
+    pub guard_level: u8,
+}
+
+impl SuperChat {
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::timestamp_to_datetime_utc8(self.start_time)
+    }
+
+    // If the SC still valid, return Some(left_display_time), otherwise return None.
+    pub fn left_display_time(&self) -> Option<i64> {
+        let left_display_time = self.end_time as i64 - chrono::Utc::now().timestamp();
+        if left_display_time <= 0 {
+            None
+        } else {
+            Some(left_display_time)
+        }
+    }
+}
+
+// The 'data' of SUPER_CHAT_MESSAGE_DELETE, the SCs are withdrawn or removed by admins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SuperChatDelete {
+    #[serde(rename = "ids")]
+    pub ids: Vec<u64>,
+}
+
+#[test]
+fn test_super_chat_jpn_serde() {
+    let json = serde_json::json!({
+        "id": "8848",
+        "uid": "10086",
+        "user_info": { "uname": "user" },
+        "message": "晚上好",
+        "message_jpn": "こんばんは",
+        "price": 30,
+        "start_time": 1674000000,
+        "end_time": 1674000060
+    });
+    let sc = SuperChat::deserialize(&json).unwrap();
+    assert_eq!((sc.id, sc.uid), (8848, 10086));
+    assert_eq!(sc.message_trans, "こんばんは");
+    assert_eq!(sc.end_time - sc.start_time, 60);
+}
@@ -2,12 +2,16 @@ pub mod api;
 pub mod app;
 pub mod client;
 pub mod config;
-pub mod message;
+pub mod error;
+pub mod event;
+#[cfg(feature = "mock-server")]
+pub mod mock;
 pub mod network;
 pub mod ui;
 pub mod utils;
 
 pub use app::*;
-pub use message::*;
+pub use error::*;
+pub use event::*;
 pub use network::*;
 pub use ui::*;
@@ -1,21 +1,42 @@
 use std::path::PathBuf;
 
+use bili_live_chat::api::live::{LiveRoom, RoomInput};
 use bili_live_chat::config::Config;
 use bili_live_chat::App;
 use bili_live_chat::Credential;
+use bili_live_chat::{Error, Result};
 use clap::{arg, command, value_parser};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (room_id, config) = cli_init();
-    let mut app = App::new(room_id, config).await;
+async fn main() -> Result<()> {
+    let (room_inputs, config) = match cli_init() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("[Error] {}", e);
+            std::process::exit(1);
+        }
+    };
+    // short ids, uids and urls are resolved to the real room ids before connecting
+    let resolving = room_inputs.into_iter().map(LiveRoom::resolve);
+    let rooms = match futures::future::try_join_all(resolving).await {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("[Error] resolve live room: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut app = match App::new(rooms, config).await {
+        Ok(app) => app,
+        // the failures of the rooms are printed by App::new
+        Err(_) => std::process::exit(1),
+    };
 
     app.run().await;
 
     Ok(())
 }
 
-fn cli_init() -> (u32, Config) {
+fn cli_init() -> Result<(Vec<RoomInput>, Config)> {
     let matches = command!()
         .arg(
             arg!(
@@ -26,9 +47,10 @@ fn cli_init() -> (u32, Config) {
         )
         .arg(
             arg!(
-                -d <ROOM_ID> "Specify a live room"
+                -d <ROOM_ID> "Specify a live room by its id, url or \"uid:<UID>\", repeat it to watch several rooms"
             )
-            .required(true),
+            .required(true)
+            .action(clap::ArgAction::Append),
         )
         .arg(
             arg!(
@@ -50,26 +72,21 @@ fn cli_init() -> (u32, Config) {
         )
         .get_matches();
 
-    let room_id = matches
-        .get_one::<String>("ROOM_ID")
-        .unwrap()
-        .parse::<u32>()
-        .unwrap_or_else(|_| panic!("Invalid Room Id."));
+    let room_inputs = matches
+        .get_many::<String>("ROOM_ID")
+        .unwrap_or_default()
+        .map(|room| room.parse())
+        .collect::<Result<Vec<RoomInput>>>()?;
 
+    let default_path = directories::BaseDirs::new().map(|dirs| {
+        dirs.home_dir()
+            .join(".config/bili-live-chat/config.yml")
+            .to_string_lossy()
+            .into_owned()
+    });
     let mut config = if let Some(path) = matches.get_one::<PathBuf>("config") {
-        let path_str = path.to_str().unwrap();
-        Config::from_file(path_str).unwrap_or_else(|_| panic!("No such file or directory"))
-    } else if let Ok(conf) = Config::from_file(
-        format!(
-            "{}/.config/bili-live-chat/config.yml",
-            directories::BaseDirs::new()
-                .unwrap()
-                .home_dir()
-                .to_str()
-                .unwrap()
-        )
-        .as_str(),
-    ) {
+        Config::from_file(&path.to_string_lossy())?
+    } else if let Some(conf) = default_path.and_then(|path| Config::from_file(&path).ok()) {
         conf
     } else {
         match (
@@ -77,18 +94,21 @@ fn cli_init() -> (u32, Config) {
             matches.get_one::<String>("bili_jct"),
             matches.get_one::<String>("buvid3"),
         ) {
-            (Some(sessdata), Some(bili_jct), Some(buvid3)) => {
-                let conf = Config {
-                    credential: Credential {
-                        session_data: sessdata.to_owned(),
-                        bili_jct: bili_jct.to_owned(),
-                        buvid3: buvid3.to_owned(),
-                    },
-                };
-
-                conf
+            (Some(sessdata), Some(bili_jct), Some(buvid3)) => Config {
+                credential: Credential {
+                    session_data: sessdata.to_owned(),
+                    bili_jct: bili_jct.to_owned(),
+                    buvid3: buvid3.to_owned(),
+                },
+                ..Default::default()
+            },
+            _ => {
+                return Err(Error::InvalidInput(
+                    "\"~/.config/bili-live-chat/config.yml\" does not exist, \
+                     and the credential is not given by arguments"
+                        .to_owned(),
+                ))
             }
-            _ => panic!("\"~/.config/bili-live-chat/config.yml\" does not exist."),
         }
     };
 
@@ -104,5 +124,5 @@ fn cli_init() -> (u32, Config) {
         config.credential.buvid3 = buvid3.clone();
     }
 
-    (room_id, config)
+    Ok((room_inputs, config))
 }
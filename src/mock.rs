@@ -0,0 +1,343 @@
+// A local mock of the danmaku server for end-to-end tests, enabled by the feature 'mock-server'.
+//
+// It serves a stand-in 'getDanmuInfo' endpoint over plain http and a websocket
+// server speaking the BiliBili live protocol, which accepts the authentication pack
+// with the right token, answers heart beats and pushes scripted command batches.
+
+use std::{
+    io::Write,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::protocol::Message as WssMessage;
+
+use crate::client::{
+    Decoder, Frame, Operation, Packet, PROTO_VER_BROTLI, PROTO_VER_INT, PROTO_VER_JSON,
+    PROTO_VER_ZLIB,
+};
+use crate::config::Config;
+use crate::Result;
+
+// The token returned by the mock 'getDanmuInfo', the authentication pack must carry it.
+pub const MOCK_TOKEN: &str = "mock-token";
+
+// The popularity carried by every heart beat response.
+pub const MOCK_POPULARITY: u32 = 1000;
+
+// How the mock server misbehaves, for the tests of failures.
+#[derive(Debug, Clone, Default)]
+pub struct MockOptions {
+    pub token: Option<String>, /* the token given by 'getDanmuInfo', MOCK_TOKEN if None */
+    pub silent_auth: bool,     /* never answer the authentication pack */
+    pub dead_hosts: usize,     /* hosts listed before the mock, which refuse connections */
+}
+
+// How the commands of a batch are packed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,   /* packs one after another, uncompressed */
+    Zlib,   /* one pack of protocol version 2 */
+    Brotli, /* one pack of protocol version 3 */
+}
+
+// Commands pushed to the clients in one websocket message.
+#[derive(Debug, Clone)]
+pub struct Batch {
+    pub compression: Compression,
+    pub commands: Vec<serde_json::Value>,
+}
+
+impl Batch {
+    pub fn new(compression: Compression, commands: Vec<serde_json::Value>) -> Self {
+        Self {
+            compression,
+            commands,
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        let packs: Vec<u8> = self
+            .commands
+            .iter()
+            .flat_map(|cmd| {
+                let body = serde_json::to_vec(cmd).unwrap();
+                Packet::new(Operation::Normal, PROTO_VER_JSON, body).into_bytes()
+            })
+            .collect();
+
+        match self.compression {
+            Compression::None => packs,
+            Compression::Zlib => {
+                let mut enc =
+                    flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+                enc.write_all(&packs).unwrap();
+                Packet::new(Operation::Normal, PROTO_VER_ZLIB, enc.finish().unwrap()).into_bytes()
+            }
+            Compression::Brotli => {
+                let mut body = vec![];
+                brotli::BrotliCompress(&mut &packs[..], &mut body, &Default::default()).unwrap();
+                Packet::new(Operation::Normal, PROTO_VER_BROTLI, body).into_bytes()
+            }
+        }
+    }
+}
+
+// What the mock server has received, for the assertions of tests.
+#[derive(Debug, Default, Clone)]
+pub struct MockRecord {
+    pub auths: Vec<serde_json::Value>, /* bodies of the authentication packs */
+    pub heart_beats: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Push {
+    Data(Option<u64>, Vec<u8>), /* None: to all the rooms */
+    Close,
+}
+
+#[derive(Debug)]
+pub struct MockServer {
+    http_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    record: Arc<Mutex<MockRecord>>,
+    push_tx: broadcast::Sender<Push>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockServer {
+    // Start the servers on random local ports.
+    // 'script' is pushed to every client right after it is authenticated.
+    pub async fn start(script: Vec<Batch>) -> Result<Self> {
+        Self::start_with(script, MockOptions::default()).await
+    }
+
+    pub async fn start_with(script: Vec<Batch>, options: MockOptions) -> Result<Self> {
+        let http = TcpListener::bind("127.0.0.1:0").await?;
+        let ws = TcpListener::bind("127.0.0.1:0").await?;
+        let (http_addr, ws_addr) = (http.local_addr()?, ws.local_addr()?);
+        let record = Arc::new(Mutex::new(MockRecord::default()));
+        let (push_tx, _) = broadcast::channel(64);
+
+        // the ports of the listeners dropped right away refuse connections
+        let mut hosts = vec![];
+        for _ in 0..options.dead_hosts {
+            hosts.push(TcpListener::bind("127.0.0.1:0").await?.local_addr()?);
+        }
+        hosts.push(ws_addr);
+        let host_list: Vec<serde_json::Value> = hosts
+            .iter()
+            .map(|addr| {
+                serde_json::json!({
+                    "host": addr.ip().to_string(),
+                    "port": addr.port(),
+                    "ws_port": addr.port(),
+                    "wss_port": 0
+                })
+            })
+            .collect();
+        let danmu_info = Arc::new(serde_json::json!({
+            "token": options.token.as_deref().unwrap_or(MOCK_TOKEN),
+            "host_list": host_list
+        }));
+
+        let http_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = http.accept().await {
+                tokio::spawn(serve_http(stream, danmu_info.clone()));
+            }
+        });
+
+        let script: Arc<Vec<Vec<u8>>> =
+            Arc::new(script.into_iter().map(Batch::into_bytes).collect());
+        let (ws_record, ws_push_tx) = (record.clone(), push_tx.clone());
+        let ws_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = ws.accept().await {
+                let push_rx = ws_push_tx.subscribe();
+                tokio::spawn(serve_ws(
+                    stream,
+                    script.clone(),
+                    options.silent_auth,
+                    ws_record.clone(),
+                    push_rx,
+                ));
+            }
+        });
+
+        Ok(Self {
+            http_addr,
+            ws_addr,
+            record,
+            push_tx,
+            tasks: vec![http_task, ws_task],
+        })
+    }
+
+    // The base url to put into 'Config::api_base'.
+    pub fn api_base(&self) -> String {
+        format!("http://{}", self.http_addr)
+    }
+
+    pub fn ws_addr(&self) -> SocketAddr {
+        self.ws_addr
+    }
+
+    // A default config pointing at the mock server.
+    pub fn config(&self) -> Config {
+        Config {
+            api_base: Some(self.api_base()),
+            ..Default::default()
+        }
+    }
+
+    // Push a batch to all the authenticated clients.
+    pub fn push(&self, batch: Batch) {
+        let _ = self.push_tx.send(Push::Data(None, batch.into_bytes()));
+    }
+
+    // Push a batch to the clients authenticated to the room.
+    pub fn push_to(&self, room_id: u32, batch: Batch) {
+        let _ = self
+            .push_tx
+            .send(Push::Data(Some(room_id as u64), batch.into_bytes()));
+    }
+
+    // Close the connections of all the clients, which are expected to reconnect.
+    pub fn close_all(&self) {
+        let _ = self.push_tx.send(Push::Close);
+    }
+
+    pub fn record(&self) -> MockRecord {
+        self.record.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        for task in self.tasks.iter() {
+            task.abort();
+        }
+    }
+}
+
+// Answer the 'getDanmuInfo' request with the token and the hosts in 'danmu_info'.
+async fn serve_http(mut stream: TcpStream, danmu_info: Arc<serde_json::Value>) {
+    // the request of the client has no body, so it ends with an empty line
+    let mut req = vec![];
+    let mut buf = [0; 1024];
+    while !req.ends_with(b"\r\n\r\n") {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => req.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    let (status, body) = if req.starts_with(b"GET /xlive/web-room/v1/index/getDanmuInfo") {
+        let body = serde_json::json!({ "code": 0, "message": "0", "data": *danmu_info });
+        ("200 OK", body)
+    } else {
+        let body = serde_json::json!({ "code": -404, "message": "啥都木有" });
+        ("404 Not Found", body)
+    };
+    let body = body.to_string();
+    let resp = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(resp.as_bytes()).await;
+}
+
+async fn serve_ws(
+    stream: TcpStream,
+    script: Arc<Vec<Vec<u8>>>,
+    silent_auth: bool,
+    record: Arc<Mutex<MockRecord>>,
+    mut push_rx: broadcast::Receiver<Push>,
+) {
+    let mut conn = match tokio_tungstenite::accept_async(stream).await {
+        Ok(conn) => conn,
+        Err(_) => return,
+    };
+    let mut decoder = Decoder::new();
+    // the room in the accepted authentication pack
+    let mut room_id = None;
+
+    loop {
+        tokio::select! {
+            msg = conn.next() => {
+                let data = match msg {
+                    Some(Ok(WssMessage::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(msg)) => msg.into_data(),
+                };
+                if decoder.feed(&data).is_err() {
+                    return;
+                }
+
+                while let Some(frame) = decoder.next_frame() {
+                    let pack = match frame {
+                        Frame::Other(pack) => pack,
+                        _ => continue,
+                    };
+                    match pack.header.opcode {
+                        Operation::Auth => {
+                            let auth: serde_json::Value =
+                                serde_json::from_slice(&pack.body).unwrap_or_default();
+                            let accepted = auth["key"] == MOCK_TOKEN;
+                            let auth_room_id = auth["roomid"].as_u64().unwrap_or_default();
+                            record.lock().unwrap().auths.push(auth);
+                            if silent_auth {
+                                continue;
+                            }
+
+                            let code = if accepted { 0 } else { -101 };
+                            let body = serde_json::json!({ "code": code }).to_string().into_bytes();
+                            let resp = Packet::new(Operation::AuthResp, PROTO_VER_INT, body);
+                            if conn.send(WssMessage::from(resp.into_bytes())).await.is_err() || !accepted {
+                                return;
+                            }
+
+                            room_id = Some(auth_room_id);
+                            for batch in script.iter() {
+                                if conn.send(WssMessage::from(batch.clone())).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Operation::HeartBeat => {
+                            record.lock().unwrap().heart_beats += 1;
+
+                            let body = MOCK_POPULARITY.to_be_bytes().to_vec();
+                            let resp = Packet::new(Operation::HeartBeatResp, PROTO_VER_INT, body);
+                            if conn.send(WssMessage::from(resp.into_bytes())).await.is_err() {
+                                return;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            push = push_rx.recv() => {
+                match push {
+                    Ok(Push::Data(target, data)) if room_id.is_some() && (target.is_none() || target == room_id) => {
+                        if conn.send(WssMessage::from(data)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(Push::Data(..)) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Ok(Push::Close) | Err(broadcast::error::RecvError::Closed) => {
+                        let _ = conn.close(None).await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -13,8 +13,8 @@ impl Credential {
         Self::default()
     }
 
-    pub fn from_vec(src: &Vec<String>) -> Option<Self> {
-        if src.len() > 3 {
+    pub fn from_vec(src: &[String]) -> Option<Self> {
+        if src.len() != 3 {
             return None;
         }
 
@@ -1,4 +1,4 @@
-use crate::network::Credential;
+use crate::{network::Credential, Error, Result};
 use std::{collections::HashMap, str::FromStr};
 
 pub struct Request {}
@@ -11,15 +11,14 @@ impl Request {
         data: Option<&mut HashMap<String, String>>,
         credential: Option<&Credential>,
         no_csrf: bool,
-    ) -> Result<reqwest::Response, reqwest::Error> {
+    ) -> Result<reqwest::Response> {
         let method = method.to_uppercase();
         let client = reqwest::Client::new();
         let mut url = url.to_owned();
 
         // From hashmap to a single string
-        let cookies = if let Some(cert) = credential {
-            cert.get_cookies()
-                .unwrap()
+        let cookies = if let Some(cookies) = credential.and_then(|cert| cert.get_cookies()) {
+            cookies
                 .iter()
                 .map(|(k, v)| format!("{}={}", k, v).replace(';', "%3B"))
                 .collect::<Vec<_>>()
@@ -28,42 +27,88 @@ impl Request {
             "".to_owned()
         };
 
-        if params.is_some() {
+        if let Some(params) = params {
             url += "?";
-            for (k, v) in params.unwrap() {
+            for (k, v) in params {
                 url += format!("{}={}&", k, v).as_str();
             }
             url.pop();
         }
 
         // Create a request builder
+        let method = reqwest::Method::from_str(&method)
+            .map_err(|_| Error::InvalidInput(format!("http method '{}'", method)))?;
+        let url = reqwest::Url::from_str(url.as_str())
+            .map_err(|e| Error::InvalidInput(format!("url '{}': {}", url, e)))?;
         let mut req_builder = client
-            .request(
-                reqwest::Method::from_str(&method).unwrap(),
-                reqwest::Url::from_str(url.as_str()).unwrap(),
-            )
+            .request(method, url)
             .header("Referer", "https://www.bilibili.com")
             .header("Origin", "https://www.bilibili.com");
 
         // Add cookies into headers of request
-        if cookies.len() > 0 {
+        if !cookies.is_empty() {
             req_builder = req_builder.header("Cookie", cookies);
         }
 
         if let Some(hm) = data {
             // Add csrf field into form
-            if !no_csrf && credential.is_some() {
-                hm.insert("csrf".to_owned(), credential.unwrap().bili_jct.clone());
-                hm.insert(
-                    "csrf_token".to_owned(),
-                    credential.unwrap().bili_jct.clone(),
-                );
+            if let (false, Some(cert)) = (no_csrf, credential) {
+                hm.insert("csrf".to_owned(), cert.bili_jct.clone());
+                hm.insert("csrf_token".to_owned(), cert.bili_jct.clone());
                 req_builder = req_builder.form(hm);
             }
         }
 
         let resp = req_builder.send().await?;
+        if !resp.status().is_success() {
+            return Err(Error::Status(resp.status().as_u16()));
+        }
 
         Ok(resp)
     }
+
+    // Send the request to a BiliBili API and return the JSON response.
+    // If the 'code' of the response is not 0, return Error::Api.
+    pub async fn send_json(
+        method: &str,
+        url: &str,
+        params: Option<&HashMap<String, String>>,
+        data: Option<&mut HashMap<String, String>>,
+        credential: Option<&Credential>,
+        no_csrf: bool,
+    ) -> Result<serde_json::Value> {
+        let resp = Self::send(method, url, params, data, credential, no_csrf).await?;
+        let json: serde_json::Value = serde_json::from_str(&resp.text().await?)?;
+        check_code(json)
+    }
+}
+
+fn check_code(json: serde_json::Value) -> Result<serde_json::Value> {
+    match json["code"].as_i64() {
+        Some(0) => Ok(json),
+        Some(code) => Err(Error::Api {
+            code,
+            message: json["message"].as_str().unwrap_or_default().to_owned(),
+        }),
+        None => Err(Error::Decode("no 'code' in the api response".to_owned())),
+    }
+}
+
+#[test]
+fn test_check_code() {
+    let json = serde_json::json!({ "code": 0, "data": { "a": 1 } });
+    assert_eq!(check_code(json).unwrap()["data"]["a"], 1);
+
+    let json = serde_json::json!({ "code": -101, "message": "账号未登录" });
+    match check_code(json) {
+        Err(Error::Api { code, message }) => {
+            assert_eq!((code, message.as_str()), (-101, "账号未登录"))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert!(matches!(
+        check_code(serde_json::json!({})),
+        Err(Error::Decode(_))
+    ));
 }
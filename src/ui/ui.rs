@@ -1,6 +1,11 @@
 use std::{collections::HashMap, process::exit, sync::Arc, time::Duration};
 
-use crate::{api::live::LiveRoom, config::Config, Message, MessageKind};
+use crate::{
+    api::live::{LiveRoom, RoomIds},
+    config::Config,
+    GuardBuy, Interact, InteractKind, LiveEvent, LotteryWinner, Notice, OnlineRankEntry,
+    PkBattleScore, RoomEvent, SuperChat,
+};
 use crossterm::{
     event::{self, DisableMouseCapture, Event, KeyCode},
     execute,
@@ -29,26 +34,61 @@ enum InputMode {
 pub struct UI<B: Backend + std::io::Write> {
     ui_state: UiState,
     terminal: Option<Terminal<B>>,
-    live_room: LiveRoom,
     config: Arc<Mutex<Config>>,
 }
 
 #[derive(Debug, Default)]
 struct UiState {
     /* Channal Receiver */
-    msg_rx: Option<Receiver<Message>>,
-    rm_info_rx: Option<Receiver<HashMap<String, String>>>,
-    rank_info_rx: Option<Receiver<Vec<String>>>,
+    msg_rx: Option<Receiver<RoomEvent>>,
+    rm_info_rx: Option<Receiver<(u32, HashMap<String, String>)>>,
+    rank_info_rx: Option<Receiver<(u32, Vec<OnlineRankEntry>)>>,
+
+    tab_selected: usize,
+
+    /* Subscribed Rooms, Switched By '[' And ']' */
+    rooms: Vec<RoomState>,
+    room_selected: usize,
+}
+
+impl UiState {
+    fn room_mut(&mut self) -> &mut RoomState {
+        &mut self.rooms[self.room_selected]
+    }
+
+    fn room_by_id(&mut self, id: u32) -> Option<&mut RoomState> {
+        self.rooms.iter_mut().find(|room| room.id == id)
+    }
+}
+
+// The state of a subscribed room.
+#[derive(Debug, Default)]
+struct RoomState {
+    // The real room id, which the events are tagged with
+    id: u32,
+    // The short id of the room, 0 if it has none
+    short_id: u32,
+    live_room: LiveRoom,
+
+    /* Banner Of WARNING, CUT_OFF And Reconnecting, dismissed by 'x' */
+    banner: Option<LiveEvent>,
 
     /* Tab 1: Chat Room */
     input_mode: InputMode,
-    tab_selected: usize,
     input_buf: String,
-    chat_history: Vec<Message>,
+    chat_history: Vec<LiveEvent>,
+    interact_history: Vec<Interact>,
+    notice_history: Vec<Notice>,
+    super_chats: Vec<SuperChat>, /* pinned until they expire */
+    sc_history: Vec<SuperChat>,  /* expired ones */
+    sc_history_scroll: usize,
+    lottery: Option<Lottery>,
+    pk_battle: Option<PkBattle>,
 
     /* Tab 2: Rank Info */
-    rank_info: Option<Vec<String>>,
-    gift_history: Vec<Message>,
+    rank_info: Option<Vec<OnlineRankEntry>>,
+    gift_history: Vec<LiveEvent>,
+    guard_history: Vec<GuardBuy>,
 
     /* Tab 3: Room Info */
     ruid: String,
@@ -58,61 +98,450 @@ struct UiState {
     description: String,
     area_name: String,
     parent_area_name: String,
+    live_status: i64, /* 0: offline, 1: live, 2: round (replaying videos) */
     live_start_time: i64,
     watched_show: i64,
+    popularity: i64,
     attention: i64,
     uname: String,
     total_likes: i64,
+    online_rank_count: i64,
+    hot_rank: RankTrend,
+    area_rank: RankTrend,
+
+    /* Stats Pushed By Websocket */
+    // Unix timestamps of the last pushes. Polled stats are only applied
+    // when the pushed ones are older than STATS_PUSH_TTL.
+    likes_pushed_at: i64,
+    watched_pushed_at: i64,
+    rank_pushed_at: i64,
+
+    /* Tab 4 (Hidden, Toggled By 'p'): Protocol */
+    // The count and the last raw payload of each unknown command
+    unknown_cmds: HashMap<String, (u64, String)>,
+}
+
+// The index of the hidden protocol tab, which is skipped by Tab.
+const PROTOCOL_TAB: usize = 3;
+
+// How many live events are handled before the next drawing at most.
+const MAX_EVENTS_PER_TICK: usize = 1024;
+
+// How long (in seconds) the stats pushed by websocket take precedence over the polled ones.
+const STATS_PUSH_TTL: i64 = 60;
+
+// A rank of the room and the one before its last change.
+#[derive(Debug, Default)]
+struct RankTrend {
+    name: String,
+    rank: u32, /* 0: not in the rank */
+    prev: u32,
+}
+
+impl RankTrend {
+    fn update(&mut self, name: String, rank: u32) {
+        self.name = name;
+        self.prev = self.rank;
+        self.rank = rank;
+    }
+}
+
+// How long (in seconds) the lottery panel is kept after the lottery ends.
+const LOTTERY_KEEP: u64 = 120;
+
+// The anchor lottery or the red envelope shown in the lottery panel.
+#[derive(Debug, Default)]
+struct Lottery {
+    id: u64,
+    title: String,
+    prize: String,
+    danmu: String,
+    require_text: String,
+    end_time: u64, /* Unix timestamp in seconds */
+    winners: Option<Vec<LotteryWinner>>,
+}
+
+// How long (in seconds) the PK scoreboard is kept after the PK ends.
+const PK_BATTLE_KEEP: u64 = 120;
+
+// The PK battle shown in the scoreboard.
+#[derive(Debug, Default)]
+struct PkBattle {
+    opponent: String,
+    opponent_room_id: u64,
+    votes_name: String,
+    votes: (u64, u64), /* (ours, the opponent's) */
+    end_time: u64,     /* Unix timestamp in seconds */
+    settled: bool,
+    // Given by PK_BATTLE_END or PK_BATTLE_SETTLE, otherwise the votes are compared
+    result: Option<std::cmp::Ordering>,
+}
+
+impl PkBattle {
+    fn update_votes(&mut self, score: &PkBattleScore, room_id: u64) {
+        let (ours, theirs) = score.sides(room_id);
+        self.votes = (ours.votes, theirs.votes);
+        self.opponent_room_id = theirs.room_id;
+    }
+}
+
+impl RoomState {
+    fn new(ids: RoomIds, live_room: LiveRoom) -> Self {
+        Self {
+            id: ids.room_id,
+            short_id: ids.short_id,
+            live_room,
+            ..Default::default()
+        }
+    }
+
+    // Both the short id and the real id, e.g. "510 (real: 80397)"
+    fn display_id(&self) -> String {
+        match self.short_id {
+            0 => self.id.to_string(),
+            short_id => format!("{} (real: {})", short_id, self.id),
+        }
+    }
+
+    // Trim the histories and hide the ended panels, called before every drawing.
+    fn tick(&mut self) {
+        // When the length of any history is greater than or equal to 100,
+        // clear up the first 50 chats to ensure that the length of chat_history
+        // is not too long.
+        if self.chat_history.len() >= 100 {
+            self.chat_history.drain(0..50);
+        }
+
+        if self.gift_history.len() >= 100 {
+            self.gift_history.drain(0..50);
+        }
+
+        if self.interact_history.len() >= 100 {
+            self.interact_history.drain(0..50);
+        }
+
+        if self.notice_history.len() >= 100 {
+            self.notice_history.drain(0..50);
+        }
+
+        if self.sc_history.len() >= 100 {
+            self.sc_history.drain(0..50);
+            self.sc_history_scroll = 0;
+        }
+
+        /* Move Expired Super Chats Into History */
+        let (pinned, expired): (Vec<_>, Vec<_>) = self
+            .super_chats
+            .drain(..)
+            .partition(|sc| sc.left_display_time().is_some());
+        self.super_chats = pinned;
+        self.sc_history.extend(expired);
+
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        /* Hide The Ended PK Battle */
+        if let Some(pk_battle) = &self.pk_battle {
+            if pk_battle.settled && now > pk_battle.end_time + PK_BATTLE_KEEP {
+                self.pk_battle = None;
+            }
+        }
+
+        /* Hide The Ended Lottery */
+        if let Some(lottery) = &self.lottery {
+            if now > lottery.end_time + LOTTERY_KEEP {
+                self.lottery = None;
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: LiveEvent) {
+        match event {
+            LiveEvent::Danmaku(_)
+            | LiveEvent::RoomBlock(_)
+            | LiveEvent::RoomSilentOn(_)
+            | LiveEvent::RoomSilentOff(_) => {
+                self.chat_history.push(event);
+            }
+            LiveEvent::Warning(_) | LiveEvent::CutOff(_) => {
+                self.banner = Some(event.clone());
+                self.chat_history.push(event);
+            }
+            LiveEvent::Reconnecting(_) => {
+                // a warning is more important than the connection status
+                if !matches!(
+                    self.banner,
+                    Some(LiveEvent::Warning(_) | LiveEvent::CutOff(_))
+                ) {
+                    self.banner = Some(event);
+                }
+            }
+            LiveEvent::Reconnected => {
+                if matches!(self.banner, Some(LiveEvent::Reconnecting(_))) {
+                    self.banner = None;
+                }
+            }
+            LiveEvent::Gift(_) | LiveEvent::ComboGift(_) => {
+                self.gift_history.push(event);
+            }
+            LiveEvent::Interact(interact) => {
+                self.interact_history.push(interact);
+            }
+            LiveEvent::Notice(notice) => {
+                self.notice_history.push(notice);
+            }
+            LiveEvent::SuperChat(sc) => {
+                // the translation may arrive before the SC itself
+                let pinned = &mut self.super_chats;
+                match pinned.iter_mut().find(|v| v.id == sc.id) {
+                    Some(v) => v.message = sc.message,
+                    None => pinned.push(sc),
+                }
+            }
+            LiveEvent::SuperChatJpn(sc) => {
+                let us = self;
+                match us
+                    .super_chats
+                    .iter_mut()
+                    .chain(us.sc_history.iter_mut())
+                    .find(|v| v.id == sc.id)
+                {
+                    Some(v) => v.message_trans = sc.message_trans,
+                    None => us.super_chats.push(sc),
+                }
+            }
+            LiveEvent::SuperChatDelete(delete) => {
+                self.super_chats.retain(|v| !delete.ids.contains(&v.id));
+                self.sc_history.retain(|v| !delete.ids.contains(&v.id));
+            }
+            LiveEvent::AnchorLotStart(lot) => {
+                self.lottery = Some(Lottery {
+                    id: lot.id,
+                    title: "天选时刻".to_owned(),
+                    prize: format!("{} x{}", lot.award_name, lot.award_num),
+                    end_time: lot.end_time(),
+                    danmu: lot.danmu,
+                    require_text: lot.require_text,
+                    winners: None,
+                });
+            }
+            LiveEvent::AnchorLotAward(award) => {
+                if let Some(lottery) = self.lottery.as_mut() {
+                    if lottery.id == award.id {
+                        lottery.end_time = chrono::Utc::now().timestamp() as u64;
+                        lottery.winners = Some(award.award_users);
+                    }
+                }
+            }
+            LiveEvent::RedPocketStart(red_pocket) => {
+                self.lottery = Some(Lottery {
+                    id: red_pocket.lot_id,
+                    title: format!("{} 的红包", red_pocket.sender_name),
+                    prize: red_pocket.awards_text(),
+                    end_time: red_pocket.end_time,
+                    danmu: red_pocket.danmu,
+                    ..Default::default()
+                });
+            }
+            LiveEvent::PkBattlePre(pre) => {
+                self.pk_battle = Some(PkBattle {
+                    opponent: pre.uname,
+                    opponent_room_id: pre.room_id,
+                    ..Default::default()
+                });
+            }
+            LiveEvent::PkBattleStart(start) => {
+                let pk_battle = self.pk_battle.get_or_insert_with(Default::default);
+                pk_battle.votes_name = start.pk_votes_name;
+                pk_battle.end_time = start.pk_end_time;
+                pk_battle.settled = false;
+            }
+            LiveEvent::PkBattleProcess(score) => {
+                // the real room id is known before the room info is polled
+                let room_id = self.id as u64;
+                let pk_battle = self.pk_battle.get_or_insert_with(Default::default);
+                pk_battle.update_votes(&score, room_id);
+            }
+            LiveEvent::PkBattleEnd(score) => {
+                // the real room id is known before the room info is polled
+                let room_id = self.id as u64;
+                let pk_battle = self.pk_battle.get_or_insert_with(Default::default);
+                pk_battle.update_votes(&score, room_id);
+                pk_battle.result = crate::pk_result(score.sides(room_id).0.winner_type);
+                pk_battle.end_time = chrono::Utc::now().timestamp() as u64;
+                pk_battle.settled = true;
+            }
+            LiveEvent::PkBattleSettle(settle) => {
+                // SETTLE carries no scores, so the votes of END are kept
+                let pk_battle = self.pk_battle.get_or_insert_with(Default::default);
+                if let Some(result) = crate::pk_result(settle.result_type) {
+                    pk_battle.result = Some(result);
+                }
+                if !pk_battle.settled {
+                    pk_battle.end_time = chrono::Utc::now().timestamp() as u64;
+                    pk_battle.settled = true;
+                }
+            }
+            LiveEvent::HotRankChanged(hot_rank) => {
+                let name = format!("{} {}", hot_rank.area_name, hot_rank.rank_desc);
+                self.hot_rank.update(name, hot_rank.rank);
+            }
+            LiveEvent::AreaRankChanged(area_rank) => {
+                self.area_rank.update(area_rank.rank_name, area_rank.rank);
+            }
+            LiveEvent::HotRankSettlement(settlement) => {
+                let name = format!("{} (settled)", settlement.area_name);
+                self.hot_rank.update(name, settlement.rank);
+            }
+            LiveEvent::Unknown(raw) => {
+                let entry = self.unknown_cmds.entry(raw.cmd).or_default();
+                entry.0 += 1;
+                entry.1 = raw.raw;
+            }
+            LiveEvent::RedPocketWinners(winners) => {
+                if let Some(lottery) = self.lottery.as_mut() {
+                    if lottery.id == winners.lot_id {
+                        lottery.end_time = chrono::Utc::now().timestamp() as u64;
+                        lottery.winners = Some(winners.winners);
+                    }
+                }
+            }
+            LiveEvent::GuardBuy(guard) => {
+                // GUARD_BUY and USER_TOAST_MSG are both sent for a purchase
+                let history = &mut self.guard_history;
+                if !history.iter().any(|g| g.is_same_purchase(&guard)) {
+                    history.push(guard);
+                }
+            }
+            LiveEvent::Popularity(popularity) => {
+                self.popularity = popularity as i64;
+            }
+            LiveEvent::LikeInfo(like_info) => {
+                self.total_likes = like_info.click_count as i64;
+                self.likes_pushed_at = chrono::Utc::now().timestamp();
+            }
+            LiveEvent::WatchedChange(watched) => {
+                self.watched_show = watched.num as i64;
+                self.watched_pushed_at = chrono::Utc::now().timestamp();
+            }
+            LiveEvent::OnlineRankCount(rank_count) => {
+                self.online_rank_count = rank_count.count as i64;
+            }
+            LiveEvent::OnlineRank(rank) => {
+                self.rank_info = Some(rank.list);
+                self.rank_pushed_at = chrono::Utc::now().timestamp();
+            }
+            LiveEvent::Live(live) => {
+                // older servers do not send the live time
+                self.live_status = 1;
+                self.live_start_time = match live.live_time {
+                    0 => chrono::Utc::now().timestamp(),
+                    live_time => live_time as i64,
+                };
+            }
+            LiveEvent::Preparing(_) => {
+                self.live_status = 0;
+                self.live_start_time = 0;
+            }
+            LiveEvent::RoomChange(room_change) => {
+                self.title = room_change.title;
+                self.area_name = room_change.area_name;
+                self.parent_area_name = room_change.parent_area_name;
+            }
+        }
+    }
+
+    fn sync_room_info(&mut self, ri: HashMap<String, String>) {
+        /* Room Info */
+        let now = chrono::Utc::now().timestamp();
+        self.ruid = ri["ruid"].clone();
+        self.room_id = ri["room_id"].clone();
+        self.title = ri["title"].clone();
+        self.tags = ri["tags"].clone();
+        self.description = ri["description"].clone();
+        self.area_name = ri["area_name"].clone();
+        self.parent_area_name = ri["parent_area_name"].clone();
+        self.live_status = ri["live_status"].parse().unwrap_or_default();
+        self.live_start_time = ri["live_start_time"].parse().unwrap_or_default();
+        if now - self.watched_pushed_at > STATS_PUSH_TTL {
+            self.watched_show = ri["watched_show"].parse().unwrap_or_default();
+        }
+        self.attention = ri["attention"].parse().unwrap_or_default();
+        self.uname = ri["uname"].clone();
+        if now - self.likes_pushed_at > STATS_PUSH_TTL {
+            self.total_likes = ri["total_likes"].parse().unwrap_or_default();
+        }
+    }
+
+    fn sync_rank_info(&mut self, rf50: Vec<OnlineRankEntry>) {
+        let now = chrono::Utc::now().timestamp();
+        if now - self.rank_pushed_at > STATS_PUSH_TTL {
+            self.rank_info = Some(rf50);
+        }
+    }
 }
 
 impl<B: Backend + std::io::Write> UI<B> {
     pub async fn new(
         term: Terminal<B>,
-        msg_rx: Receiver<Message>,
-        rm_info_rx: Receiver<HashMap<String, String>>,
-        rank_info_rx: Receiver<Vec<String>>,
-        room_id: i64,
+        msg_rx: Receiver<RoomEvent>,
+        rm_info_rx: Receiver<(u32, HashMap<String, String>)>,
+        rank_info_rx: Receiver<(u32, Vec<OnlineRankEntry>)>,
+        rooms: &[RoomIds],
         config: Arc<Mutex<Config>>,
     ) -> Self {
+        assert!(!rooms.is_empty(), "Err: No live room to show!!!");
+
+        let credential = config.lock().await.credential.clone();
+        let rooms = rooms
+            .iter()
+            .map(|&ids| RoomState::new(ids, LiveRoom::new(ids.room_id as i64, credential.clone())))
+            .collect();
         let state = UiState {
             msg_rx: Some(msg_rx),
             rm_info_rx: Some(rm_info_rx),
             rank_info_rx: Some(rank_info_rx),
+            rooms,
             ..Default::default()
         };
 
-        let live_room = LiveRoom::new(room_id, config.lock().await.credential.clone());
-
         Self {
             terminal: Some(term),
             ui_state: state,
-            live_room,
             config,
         }
     }
 
+    fn room_next(&mut self) {
+        self.ui_state.room_selected = (self.ui_state.room_selected + 1) % self.ui_state.rooms.len();
+    }
+
+    fn room_prev(&mut self) {
+        let len = self.ui_state.rooms.len();
+        self.ui_state.room_selected = (self.ui_state.room_selected + len - 1) % len;
+    }
+
     fn tab_next(&mut self) {
         self.ui_state.tab_selected += 1;
-        if self.ui_state.tab_selected > 2 {
+        if self.ui_state.tab_selected >= PROTOCOL_TAB {
             self.ui_state.tab_selected = 0;
         }
     }
 
+    fn toggle_protocol_tab(&mut self) {
+        self.ui_state.tab_selected = match self.ui_state.tab_selected {
+            PROTOCOL_TAB => 0,
+            _ => PROTOCOL_TAB,
+        };
+    }
+
     pub async fn run(&mut self) -> std::io::Result<()> {
         if self.terminal.is_none() {
             panic!("Err: The terminal af TUI invalid!!!");
         }
 
         loop {
-            // When the length of chat_history and gift_history is greater than or equal to 100,
-            // clear up the first 50 chats to ensure that the length of chat_history
-            // is not too long.
-            if self.ui_state.chat_history.len() >= 100 {
-                self.ui_state.chat_history.drain(0..50);
-            }
-
-            if self.ui_state.gift_history.len() >= 100 {
-                self.ui_state.gift_history.drain(0..50);
+            for room in self.ui_state.rooms.iter_mut() {
+                room.tick();
             }
 
             /* Draw UI */
@@ -121,48 +550,41 @@ impl<B: Backend + std::io::Write> UI<B> {
                 .unwrap()
                 .draw(|f| draw_ui(f, &mut self.ui_state))?;
 
-            /* Receive Message */
-            if let Ok(msg) = self.ui_state.msg_rx.as_mut().unwrap().try_recv() {
-                match msg.kind {
-                    MessageKind::DANMU_MSG => {
-                        self.ui_state.chat_history.push(msg);
-                    }
-                    MessageKind::SEND_GIFT => {
-                        self.ui_state.gift_history.push(msg);
-                    }
-                    _ => {}
+            /* Receive Live Events */
+            // Drain the channel, or the clients are blocked when the rooms are busy.
+            for _ in 0..MAX_EVENTS_PER_TICK {
+                let RoomEvent { room_id, event } =
+                    match self.ui_state.msg_rx.as_mut().unwrap().try_recv() {
+                        Ok(room_event) => room_event,
+                        Err(_) => break,
+                    };
+                if let Some(room) = self.ui_state.room_by_id(room_id) {
+                    room.handle_event(event);
                 }
             }
 
             /* Sync Room Info */
-            if let Ok(ri) = self.ui_state.rm_info_rx.as_mut().unwrap().try_recv() {
-                /* Room Info */
-                self.ui_state.ruid = ri["ruid"].clone();
-                self.ui_state.room_id = ri["room_id"].clone();
-                self.ui_state.title = ri["title"].clone();
-                self.ui_state.tags = ri["tags"].clone();
-                self.ui_state.description = ri["description"].clone();
-                self.ui_state.area_name = ri["area_name"].clone();
-                self.ui_state.parent_area_name = ri["parent_area_name"].clone();
-                self.ui_state.live_start_time = ri["live_start_time"].parse().unwrap();
-                self.ui_state.watched_show = ri["watched_show"].parse().unwrap();
-                self.ui_state.attention = ri["attention"].parse().unwrap();
-                self.ui_state.uname = ri["uname"].clone();
-                self.ui_state.total_likes = ri["total_likes"].parse().unwrap();
+            if let Ok((room_id, ri)) = self.ui_state.rm_info_rx.as_mut().unwrap().try_recv() {
+                if let Some(room) = self.ui_state.room_by_id(room_id) {
+                    room.sync_room_info(ri);
+                }
             }
 
             /* Sync The First 50 Of Rank Info */
-            if let Ok(rf50) = self.ui_state.rank_info_rx.as_mut().unwrap().try_recv() {
-                self.ui_state.rank_info = Some(rf50);
+            if let Ok((room_id, rf50)) = self.ui_state.rank_info_rx.as_mut().unwrap().try_recv() {
+                if let Some(room) = self.ui_state.room_by_id(room_id) {
+                    room.sync_rank_info(rf50);
+                }
             }
 
             /* Poll Keyboard Events */
             if crossterm::event::poll(Duration::from_millis(10)).unwrap() {
                 if let Event::Key(key) = event::read()? {
-                    match self.ui_state.input_mode {
+                    let room = self.ui_state.room_mut();
+                    match room.input_mode {
                         InputMode::Normal => match key.code {
                             KeyCode::Char('e') => {
-                                self.ui_state.input_mode = InputMode::Editing;
+                                room.input_mode = InputMode::Editing;
                             }
                             KeyCode::Char('q') => {
                                 /* restore terminal */
@@ -176,31 +598,56 @@ impl<B: Backend + std::io::Write> UI<B> {
                             KeyCode::Tab => {
                                 self.tab_next();
                             }
+                            KeyCode::Char(']') => {
+                                self.room_next();
+                            }
+                            KeyCode::Char('[') => {
+                                self.room_prev();
+                            }
+                            KeyCode::Char('p') => {
+                                self.toggle_protocol_tab();
+                            }
+                            KeyCode::Char('x') => {
+                                room.banner = None;
+                            }
+                            KeyCode::Up => {
+                                // scroll the SC history to the older ones
+                                let max_scroll = room.sc_history.len().saturating_sub(1);
+                                if room.sc_history_scroll < max_scroll {
+                                    room.sc_history_scroll += 1;
+                                }
+                            }
+                            KeyCode::Down => {
+                                room.sc_history_scroll = room.sc_history_scroll.saturating_sub(1);
+                            }
                             _ => {}
                         },
                         InputMode::Editing => match key.code {
                             KeyCode::Esc => {
-                                self.ui_state.input_mode = InputMode::Normal;
+                                room.input_mode = InputMode::Normal;
                             }
                             KeyCode::Char(c) => {
-                                self.ui_state.input_buf.push(c);
+                                room.input_buf.push(c);
                             }
                             KeyCode::Backspace => {
-                                self.ui_state.input_buf.pop();
+                                room.input_buf.pop();
                             }
-                            KeyCode::Enter => {
-                                if self.ui_state.input_buf.len() > 0 {
-                                    let danmaku_text =
-                                        self.ui_state.input_buf.drain(..).collect::<String>();
-                                    // refresh ui immediately
-                                    self.terminal
-                                        .as_mut()
-                                        .unwrap()
-                                        .draw(|f| draw_ui(f, &mut self.ui_state))?;
-
-                                    self.live_room
-                                        .send_normal_danmaku(danmaku_text.as_str())
-                                        .await;
+                            KeyCode::Enter if !room.input_buf.is_empty() => {
+                                let danmaku_text = room.input_buf.drain(..).collect::<String>();
+                                // refresh ui immediately
+                                self.terminal
+                                    .as_mut()
+                                    .unwrap()
+                                    .draw(|f| draw_ui(f, &mut self.ui_state))?;
+
+                                if let Err(e) = self
+                                    .ui_state
+                                    .room_mut()
+                                    .live_room
+                                    .send_normal_danmaku(danmaku_text.as_str())
+                                    .await
+                                {
+                                    eprintln!("[Error] send_normal_danmaku: {}", e);
                                 }
                             }
                             _ => {}
@@ -213,16 +660,36 @@ impl<B: Backend + std::io::Write> UI<B> {
 }
 
 fn draw_ui<B: Backend>(f: &mut Frame<B>, us: &mut UiState) {
+    let rooms_height = if us.rooms.len() > 1 { 1 } else { 0 };
+    let banner_height = if us.rooms[us.room_selected].banner.is_some() {
+        3
+    } else {
+        0
+    };
     let chunks = Layout::default()
         .direction(tui::layout::Direction::Vertical)
         .margin(0)
-        .constraints([Constraint::Length(1), Constraint::Min(3)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(rooms_height),
+                Constraint::Length(1),
+                Constraint::Length(banner_height),
+                Constraint::Min(3),
+            ]
+            .as_ref(),
+        )
         .split(f.size());
-    let tabs_title = vec![
+    if us.rooms.len() > 1 {
+        draw_room_tabs(f, us, chunks[0]);
+    }
+    let mut tabs_title = vec![
         "Chat Room".to_owned(),
         "Rank Info".to_owned(),
         "Room Info".to_owned(),
     ];
+    if us.tab_selected == PROTOCOL_TAB {
+        tabs_title.push("Protocol".to_owned());
+    }
     let tabs_title = tabs_title
         .iter()
         .enumerate()
@@ -238,29 +705,179 @@ fn draw_ui<B: Backend>(f: &mut Frame<B>, us: &mut UiState) {
         })
         .collect();
     let tabs = Tabs::new(tabs_title).select(us.tab_selected);
-    f.render_widget(tabs, chunks[0]);
+    let bar_chunks = Layout::default()
+        .direction(tui::layout::Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(10)].as_ref())
+        .split(chunks[1]);
+    f.render_widget(tabs, bar_chunks[0]);
+
+    let tab_selected = us.tab_selected;
+    let room = &mut us.rooms[us.room_selected];
+    f.render_widget(Paragraph::new(live_status_badge(room)), bar_chunks[1]);
 
-    match us.tab_selected {
-        0 => draw_chat_room(f, us, chunks[1]),
-        1 => draw_rank_info(f, us, chunks[1]),
-        2 => draw_room_info(f, us, chunks[1]),
+    draw_banner(f, room, chunks[2]);
+
+    match tab_selected {
+        0 => draw_chat_room(f, room, chunks[3]),
+        1 => draw_rank_info(f, room, chunks[3]),
+        2 => draw_room_info(f, room, chunks[3]),
+        PROTOCOL_TAB => draw_protocol(f, room, chunks[3]),
         _ => unreachable!(),
     };
 }
 
-fn draw_chat_room<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
+fn draw_room_tabs<B: Backend>(f: &mut Frame<B>, us: &UiState, area: Rect) {
+    let titles = us
+        .rooms
+        .iter()
+        .enumerate()
+        .map(|(index, room)| {
+            let title = if room.uname.is_empty() {
+                room.display_id()
+            } else {
+                format!("{} {}", room.display_id(), room.uname)
+            };
+            if index == us.room_selected {
+                Spans::from(Span::styled(title, Style::default().fg(Color::Blue)))
+            } else {
+                Spans::from(Span::from(title))
+            }
+        })
+        .collect();
+    let tabs = Tabs::new(titles).select(us.room_selected);
     let chunks = Layout::default()
-        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+        .direction(tui::layout::Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(10)].as_ref())
+        .split(area);
+    f.render_widget(tabs, chunks[0]);
+    f.render_widget(
+        Paragraph::new(Span::styled("[ / ]", Style::default().fg(Color::Gray))),
+        chunks[1],
+    );
+}
+
+fn live_status_badge(us: &RoomState) -> Span<'static> {
+    match us.live_status {
+        1 => Span::styled("● LIVE", Style::default().fg(Color::Red)),
+        2 => Span::styled("◐ ROUND", Style::default().fg(Color::Yellow)),
+        _ => Span::styled("○ OFFLINE", Style::default().fg(Color::Gray)),
+    }
+}
+
+fn draw_banner<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let warning_style = Style::default().fg(Color::White).bg(Color::Red);
+    let (title, msg, style) = match &us.banner {
+        Some(LiveEvent::Warning(warning)) => ("超管警告", warning.msg.clone(), warning_style),
+        Some(LiveEvent::CutOff(cut_off)) => ("直播被切断", cut_off.msg.clone(), warning_style),
+        Some(LiveEvent::Reconnecting(reconnecting)) => (
+            "连接断开",
+            format!(
+                "正在重连 (已重试 {} 次): {}",
+                reconnecting.retries, reconnecting.reason
+            ),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ),
+        _ => return,
+    };
+    let banner = Paragraph::new(msg).style(style).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} (press 'x' to dismiss)", title)),
+    );
+    f.render_widget(banner, area);
+}
+
+fn draw_chat_room<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let h_chunks = Layout::default()
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .direction(tui::layout::Direction::Horizontal)
         .split(area);
+    let pinned_items = us
+        .super_chats
+        .iter()
+        .flat_map(|sc| {
+            let left = sc.left_display_time().unwrap_or_default();
+            let mut lines = vec![ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!(" ¥{} {:02}:{:02} ", sc.price, left / 60, left % 60),
+                    Style::default().fg(Color::Black).bg(sc_color(sc.price)),
+                ),
+                Span::styled(
+                    format!(" {}: ", sc.user_info.uname),
+                    Style::default().fg(Color::Blue),
+                ),
+                Span::raw(sc.message.clone()),
+            ]))];
+            if !sc.message_trans.is_empty() {
+                lines.push(
+                    ListItem::new(format!("  {}", sc.message_trans))
+                        .style(Style::default().fg(Color::Gray)),
+                );
+            }
+            lines
+        })
+        .collect::<Vec<_>>();
+    let pinned_height = match pinned_items.len() {
+        0 => 0,
+        len => len.min(6) as u16 + 2,
+    };
+    let lottery_height = if us.lottery.is_some() { 5 } else { 0 };
+    let pk_battle_height = if us.pk_battle.is_some() { 5 } else { 0 };
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Length(pinned_height),
+                Constraint::Length(lottery_height),
+                Constraint::Length(pk_battle_height),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ]
+            .as_ref(),
+        )
+        .split(h_chunks[0]);
+
+    /* Pinned Super Chats */
+    let pinned_list =
+        List::new(pinned_items).block(Block::default().borders(Borders::ALL).title("Super Chat"));
+    f.render_widget(pinned_list, chunks[0]);
+
+    draw_lottery(f, us, chunks[1]);
+    draw_pk_battle(f, us, chunks[2]);
+    let chunks = &chunks[3..];
 
     /* Chat History */
     let mut chat_history = vec![];
-    for msg in us.chat_history.iter() {
-        let title = format!("[{}] {}", msg.date.format("%H:%M"), msg.author);
+    for event in us.chat_history.iter() {
+        if let LiveEvent::Danmaku(danmaku) = event {
+            let title = format!("[{}] {}", danmaku.date().format("%H:%M"), danmaku.uname);
+            chat_history.push(
+                ListItem::new(Text::from(Spans::from(title)))
+                    .style(Style::default().fg(Color::Cyan)),
+            );
+            chat_history.push(ListItem::new(Text::from(Spans::from(
+                danmaku.content.clone(),
+            ))));
+            continue;
+        }
+
+        let (text, color) = match event {
+            LiveEvent::RoomBlock(block) => (
+                format!("{} 已被{}禁言", block.uname, block.operator_name()),
+                Color::Yellow,
+            ),
+            LiveEvent::RoomSilentOn(silent) => (
+                format!("直播间开启了禁言: {}", silent.description()),
+                Color::Yellow,
+            ),
+            LiveEvent::RoomSilentOff(_) => ("直播间关闭了禁言".to_owned(), Color::Yellow),
+            LiveEvent::Warning(warning) => (format!("超管警告: {}", warning.msg), Color::Red),
+            LiveEvent::CutOff(cut_off) => (format!("直播被切断: {}", cut_off.msg), Color::Red),
+            _ => continue,
+        };
         chat_history.push(
-            ListItem::new(Text::from(Spans::from(title))).style(Style::default().fg(Color::Cyan)),
+            ListItem::new(Text::from(Spans::from(format!("[mod] {}", text))))
+                .style(Style::default().fg(color)),
         );
-        chat_history.push(ListItem::new(Text::from(Spans::from(msg.content.clone()))));
     }
     chat_history.reverse();
     let chat_history = List::new(chat_history)
@@ -279,40 +896,229 @@ fn draw_chat_room<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
             f.set_cursor(chunks[1].x + len as u16 + 1, chunks[1].y + 1);
         }
     }
+
+    draw_activity(f, us, h_chunks[1]);
 }
 
-fn draw_rank_info<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
+fn draw_lottery<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let lottery = match &us.lottery {
+        Some(lottery) => lottery,
+        None => return,
+    };
+
+    let mut danmu = vec![
+        Span::raw("弹幕: "),
+        Span::styled(lottery.danmu.as_str(), Style::default().fg(Color::Cyan)),
+    ];
+    if !lottery.require_text.is_empty() {
+        danmu.push(Span::raw(format!(" ({})", lottery.require_text)));
+    }
+    let status = match &lottery.winners {
+        Some(winners) if winners.is_empty() => Spans::from("中奖: 无人中奖"),
+        Some(winners) => Spans::from(vec![
+            Span::raw("中奖: "),
+            Span::styled(
+                winners
+                    .iter()
+                    .map(|v| v.uname.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Style::default().fg(Color::Green),
+            ),
+        ]),
+        None => {
+            let left = lottery
+                .end_time
+                .saturating_sub(chrono::Utc::now().timestamp() as u64);
+            Spans::from(format!("倒计时: {:02}:{:02}", left / 60, left % 60))
+        }
+    };
+    let text = vec![
+        Spans::from(vec![
+            Span::raw("奖品: "),
+            Span::styled(lottery.prize.as_str(), Style::default().fg(Color::Yellow)),
+        ]),
+        Spans::from(danmu),
+        status,
+    ];
+    let panel = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(lottery.title.as_str()),
+    );
+    f.render_widget(panel, area);
+}
+
+fn draw_pk_battle<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let pk_battle = match &us.pk_battle {
+        Some(pk_battle) => pk_battle,
+        None => return,
+    };
+
+    let (ours, theirs) = pk_battle.votes;
+    let votes_name = match pk_battle.votes_name.as_str() {
+        "" => "PK值",
+        name => name,
+    };
+    let scores = Spans::from(vec![
+        Span::styled(format!("我方 {} ", ours), Style::default().fg(Color::Blue)),
+        Span::raw(format!("{} ", votes_name)),
+        Span::styled(format!("{} 对方", theirs), Style::default().fg(Color::Red)),
+    ]);
+    // Split the bar by the scores, half and half before anyone scores.
+    let bar_width = area.width.saturating_sub(2) as u64;
+    let ours_width = match ours + theirs {
+        0 => bar_width / 2,
+        total => bar_width * ours / total,
+    } as usize;
+    let bar = Spans::from(vec![
+        Span::styled("█".repeat(ours_width), Style::default().fg(Color::Blue)),
+        Span::styled(
+            "█".repeat(bar_width as usize - ours_width),
+            Style::default().fg(Color::Red),
+        ),
+    ]);
+    let status = if pk_battle.settled {
+        let (result, color) = match pk_battle.result.unwrap_or_else(|| ours.cmp(&theirs)) {
+            std::cmp::Ordering::Greater => ("胜利", Color::Green),
+            std::cmp::Ordering::Less => ("失败", Color::Red),
+            std::cmp::Ordering::Equal => ("平局", Color::Yellow),
+        };
+        Spans::from(vec![
+            Span::raw("结果: "),
+            Span::styled(result, Style::default().fg(color)),
+        ])
+    } else {
+        let left = pk_battle
+            .end_time
+            .saturating_sub(chrono::Utc::now().timestamp() as u64);
+        Spans::from(format!("剩余: {:02}:{:02}", left / 60, left % 60))
+    };
+
+    let title = match pk_battle.opponent.as_str() {
+        "" => format!("PK vs 房间 {}", pk_battle.opponent_room_id),
+        opponent => format!("PK vs {}", opponent),
+    };
+    let panel = Paragraph::new(vec![scores, bar, status])
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(panel, area);
+}
+
+// The colour of a super chat by its price tier.
+fn sc_color(price: u64) -> Color {
+    match price {
+        0..=49 => Color::Blue,
+        50..=99 => Color::Cyan,
+        100..=499 => Color::Yellow,
+        500..=999 => Color::LightRed,
+        1000..=1999 => Color::Red,
+        _ => Color::Magenta,
+    }
+}
+
+fn draw_activity<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage(40),
+                Constraint::Percentage(25),
+                Constraint::Percentage(35),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    /* interactions: enter, follow, share... */
+    let mut interact_items = us
+        .interact_history
+        .iter()
+        .map(|v| {
+            let (action, color) = match v.kind {
+                InteractKind::Enter => ("entered", Color::Gray),
+                InteractKind::Follow => ("followed", Color::Green),
+                InteractKind::Share => ("shared", Color::Yellow),
+                InteractKind::SpecialFollow => ("special followed", Color::Green),
+                InteractKind::MutualFollow => ("mutual followed", Color::Green),
+                InteractKind::Like => ("liked", Color::Magenta),
+                InteractKind::Unknown(_) => ("interacted", Color::Gray),
+            };
+            ListItem::new(Spans::from(vec![
+                Span::raw(v.date().format("[%H:%M]").to_string()),
+                Span::styled(
+                    " ".to_owned() + v.uname.as_str() + " ",
+                    Style::default().fg(Color::Blue),
+                ),
+                Span::styled(action, Style::default().fg(color)),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    interact_items.reverse();
+    let interact_list = List::new(interact_items)
+        .block(Block::default().borders(Borders::ALL).title("Interactions"))
+        .start_corner(tui::layout::Corner::BottomLeft);
+    f.render_widget(interact_list, chunks[0]);
+
+    /* system broadcast notices */
+    let mut notice_items = us
+        .notice_history
+        .iter()
+        .map(|v| ListItem::new(Text::from(v.text())).style(Style::default().fg(Color::Yellow)))
+        .collect::<Vec<_>>();
+    notice_items.reverse();
+    let notice_list = List::new(notice_items)
+        .block(Block::default().borders(Borders::ALL).title("Notices"))
+        .start_corner(tui::layout::Corner::BottomLeft);
+    f.render_widget(notice_list, chunks[1]);
+
+    /* expired super chats, scrolled by Up and Down */
+    let sc_items = us
+        .sc_history
+        .iter()
+        .rev()
+        .skip(us.sc_history_scroll)
+        .map(|sc| {
+            let mut text = Text::from(format!(
+                "{} ¥{} {}: {}",
+                sc.date().format("[%H:%M]"),
+                sc.price,
+                sc.user_info.uname,
+                sc.message
+            ));
+            if !sc.message_trans.is_empty() {
+                text.extend(Text::from(format!("  {}", sc.message_trans)));
+            }
+            ListItem::new(text).style(Style::default().fg(sc_color(sc.price)))
+        })
+        .collect::<Vec<_>>();
+    let sc_list = List::new(sc_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Super Chats (Up/Down)"),
+        )
+        .start_corner(tui::layout::Corner::BottomLeft);
+    f.render_widget(sc_list, chunks[2]);
+}
+
+fn draw_rank_info<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
     let chunks = Layout::default()
         .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
         .direction(tui::layout::Direction::Horizontal)
         .split(area);
 
     /* rank info */
-    let rank_info_items = {
-        if let Some(ref data) = us.rank_info {
-            Some(
-                // format: name,guard_level,score
-                data.iter()
-                    .map(|i| i.split(',').collect::<Vec<_>>())
-                    .collect::<Vec<_>>(),
-            )
-        } else {
-            None
-        }
-    };
     let mut list_items = vec![];
-    if let Some(data) = rank_info_items {
+    if let Some(ref data) = us.rank_info {
         for (k, v) in data.iter().enumerate() {
-            let rank;
-            if k < 3 {
-                rank = Span::styled((k + 1).to_string() + ": ", Style::default().fg(Color::Red));
+            let rank = if k < 3 {
+                Span::styled((k + 1).to_string() + ": ", Style::default().fg(Color::Red))
             } else {
-                rank = Span::raw((k + 1).to_string() + ": ");
-            }
+                Span::raw((k + 1).to_string() + ": ")
+            };
             let spans = Spans::from(vec![
                 rank,
-                Span::styled(v[0].to_owned() + " ", Style::default().fg(Color::Cyan)),
-                Span::styled(v[2].to_owned(), Style::default().fg(Color::Blue)),
+                Span::styled(v.uname.to_owned() + " ", Style::default().fg(Color::Cyan)),
+                Span::styled(v.score.to_string(), Style::default().fg(Color::Blue)),
             ]);
             list_items.push(ListItem::new(Text::from(spans)));
         }
@@ -324,44 +1130,105 @@ fn draw_rank_info<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
         List::new(list_items).block(Block::default().borders(Borders::ALL).title("Rank"));
     f.render_widget(rank_info_list, chunks[0]);
 
+    let r_chunks = Layout::default()
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(chunks[1]);
+
+    /* guard purchases during the session */
+    let mut guard_items = us
+        .guard_history
+        .iter()
+        .map(|v| {
+            let color = match v.guard_level {
+                1 => Color::Red,
+                2 => Color::Magenta,
+                _ => Color::Cyan,
+            };
+            ListItem::new(Spans::from(vec![
+                Span::raw(v.date().format("[%H:%M]").to_string()),
+                Span::styled(
+                    " ".to_owned() + v.username.as_str() + " ",
+                    Style::default().fg(Color::Blue),
+                ),
+                Span::styled(
+                    format!(
+                        "{} x{}{} ({} CNY)",
+                        v.guard_name(),
+                        v.num,
+                        v.unit,
+                        v.price * v.num as u64 / 1000
+                    ),
+                    Style::default().fg(color),
+                ),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    guard_items.reverse();
+    let guard_list = List::new(guard_items)
+        .block(Block::default().borders(Borders::ALL).title("Guards"))
+        .start_corner(tui::layout::Corner::BottomLeft);
+    f.render_widget(guard_list, r_chunks[0]);
+
     /* gift */
     let mut gift_items = us
         .gift_history
         .iter()
-        .map(|v| {
-            let datetime = v.date.format("[%H:%M]").to_string();
+        .filter_map(|v| {
+            // TODO: implment i18n
+            let (datetime, uname, content) = match v {
+                LiveEvent::Gift(gift) => (
+                    gift.date().format("[%H:%M]").to_string(),
+                    gift.uname.as_str(),
+                    format!("{}了{}个{}", gift.action, gift.num, gift.gift_name),
+                ),
+                // combos carry the running totals of the gifts
+                LiveEvent::ComboGift(combo) => (
+                    "[combo]".to_owned(),
+                    combo.uname.as_str(),
+                    format!(
+                        "{}了{}个{} x{} (共{}个)",
+                        combo.action,
+                        combo.gift_num,
+                        combo.gift_name,
+                        combo.combo_num,
+                        combo.total_num
+                    ),
+                ),
+                _ => return None,
+            };
             let gift_ctnt = Spans::from(vec![
                 Span::raw(datetime),
                 Span::styled(
-                    " ".to_owned() + v.author.as_str() + " ",
+                    " ".to_owned() + uname + " ",
                     Style::default().fg(Color::Blue),
                 ),
-                Span::styled(v.content.clone(), Style::default().fg(Color::Green)),
+                Span::styled(content, Style::default().fg(Color::Green)),
             ]);
-            ListItem::new(gift_ctnt)
+            Some(ListItem::new(gift_ctnt))
         })
         .collect::<Vec<_>>();
     gift_items.reverse();
     let gift_list = List::new(gift_items)
         .block(Block::default().borders(Borders::ALL).title("Gift"))
         .start_corner(tui::layout::Corner::BottomLeft);
-    f.render_widget(gift_list, chunks[1]);
+    f.render_widget(gift_list, r_chunks[1]);
 }
 
-fn draw_room_info<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
+fn draw_room_info<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
     let chunks = Layout::default()
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(area);
 
     /* base info */
     let mut text = vec![
+        Spans::from(vec![Span::raw("Status: "), live_status_badge(us)]),
         Spans::from(vec![
             Span::raw("Host: "),
             Span::styled(us.uname.clone(), Style::default().fg(Color::Green)),
         ]),
         Spans::from(vec![
             Span::raw("Room Id: "),
-            Span::styled(us.room_id.clone(), Style::default().fg(Color::Cyan)),
+            Span::styled(us.display_id(), Style::default().fg(Color::Cyan)),
         ]),
         Spans::from(vec![
             Span::raw("Title: "),
@@ -383,14 +1250,17 @@ fn draw_room_info<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
     f.render_widget(base_info, chunks[0]);
 
     /* other info */
-    let duration = crate::utils::duration(us.live_start_time as u64);
+    let duration = match (
+        us.live_status,
+        crate::utils::duration(us.live_start_time as u64),
+    ) {
+        (1, Some(duration)) => crate::utils::display_duration(duration),
+        _ => "Not live".to_owned(),
+    };
     let text = vec![
         Spans::from(vec![
             Span::raw("Live duration: "),
-            Span::styled(
-                crate::utils::display_duration(duration),
-                Style::default().fg(Color::Red),
-            ),
+            Span::styled(duration, Style::default().fg(Color::Red)),
         ]),
         Spans::from(vec![
             Span::raw("Total likes: "),
@@ -404,11 +1274,87 @@ fn draw_room_info<B: Backend>(f: &mut Frame<B>, us: &mut UiState, area: Rect) {
             Span::raw("Watched show: "),
             Span::styled(us.watched_show.to_string(), Style::default().fg(Color::Red)),
         ]),
+        Spans::from(vec![
+            Span::raw("Online rank count: "),
+            Span::styled(
+                us.online_rank_count.to_string(),
+                Style::default().fg(Color::Red),
+            ),
+        ]),
+        Spans::from(vec![
+            Span::raw("Popularity: "),
+            Span::styled(us.popularity.to_string(), Style::default().fg(Color::Red)),
+        ]),
+        rank_trend_spans("Hot rank: ", &us.hot_rank),
+        rank_trend_spans("Area rank: ", &us.area_rank),
     ];
     let other_info = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
     f.render_widget(other_info, chunks[1]);
 }
 
+fn draw_protocol<B: Backend>(f: &mut Frame<B>, us: &mut RoomState, area: Rect) {
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .direction(tui::layout::Direction::Horizontal)
+        .split(area);
+
+    // the most frequent commands first
+    let mut cmds = us.unknown_cmds.iter().collect::<Vec<_>>();
+    cmds.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(b.0)));
+
+    let freq_items = cmds
+        .iter()
+        .map(|(cmd, (count, _))| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{:>6} ", count), Style::default().fg(Color::Yellow)),
+                Span::raw(cmd.as_str()),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    let freq_list = List::new(freq_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Unknown Commands"),
+    );
+    f.render_widget(freq_list, chunks[0]);
+
+    let payload_items = cmds
+        .iter()
+        .map(|(cmd, (_, raw))| {
+            let mut text = Text::styled(cmd.as_str(), Style::default().fg(Color::Cyan));
+            text.extend(Text::raw(raw.as_str()));
+            ListItem::new(text)
+        })
+        .collect::<Vec<_>>();
+    let payload_list = List::new(payload_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Last Payloads"),
+    );
+    f.render_widget(payload_list, chunks[1]);
+}
+
+// e.g. "Hot rank: 3 ▲ (虚拟主播 限时热门榜)"
+fn rank_trend_spans<'a>(label: &'a str, trend: &'a RankTrend) -> Spans<'a> {
+    if trend.rank == 0 {
+        return Spans::from(vec![Span::raw(label), Span::raw("-")]);
+    }
+
+    // a smaller number is a higher rank
+    let indicator = match (trend.prev, trend.rank) {
+        (0, _) => Span::raw(""),
+        (prev, rank) if rank < prev => Span::styled(" ▲", Style::default().fg(Color::Green)),
+        (prev, rank) if rank > prev => Span::styled(" ▼", Style::default().fg(Color::Red)),
+        _ => Span::styled(" -", Style::default().fg(Color::Gray)),
+    };
+    Spans::from(vec![
+        Span::raw(label),
+        Span::styled(trend.rank.to_string(), Style::default().fg(Color::Red)),
+        indicator,
+        Span::raw(format!(" ({})", trend.name)),
+    ])
+}
+
 impl<B: Backend + std::io::Write> Drop for UI<B> {
     fn drop(&mut self) {
         /* restore terminal */
@@ -418,3 +1364,30 @@ impl<B: Backend + std::io::Write> Drop for UI<B> {
         self.terminal.as_mut().unwrap().show_cursor().unwrap();
     }
 }
+
+#[test]
+fn test_pk_battle_end_then_settle() {
+    // we are the matched room, and the room info is not polled yet
+    let mut room = RoomState {
+        id: 21452505,
+        ..Default::default()
+    };
+    let end = serde_json::json!({
+        "cmd": "PK_BATTLE_END",
+        "data": {
+            "init_info": { "room_id": 3044248, "votes": 80, "winner_type": -1 },
+            "match_info": { "room_id": 21452505, "votes": 120, "winner_type": 2 }
+        }
+    });
+    let settle = serde_json::json!({
+        "cmd": "PK_BATTLE_SETTLE",
+        "data": { "battle_type": 1, "result_type": 2, "star_light_msg": "" }
+    });
+    room.handle_event(LiveEvent::from_command(&end).unwrap());
+    room.handle_event(LiveEvent::from_command(&settle).unwrap());
+
+    let pk_battle = room.pk_battle.unwrap();
+    assert_eq!(pk_battle.votes, (120, 80));
+    assert_eq!(pk_battle.result, Some(std::cmp::Ordering::Greater));
+    assert!(pk_battle.settled);
+}
@@ -2,77 +2,35 @@ use chrono;
 use flate2::read::ZlibDecoder;
 use std::io::prelude::*;
 
-use crate::client::DataPack;
-
-pub fn fill_datapack_header(type_pack: DataPack, data_pack: &mut [u8], sequence: u32) {
-    let pack_len = data_pack.len() as u32;
-    for (i, byte) in pack_len.to_be_bytes().iter().enumerate() {
-        data_pack[i] = *byte;
-    }
-    let head_len: u16 = 16;
-    let mut ver: u16 = 0;
-    let mut op_code: u32 = 0;
-    let mut offset = 4;
-
-    match type_pack {
-        DataPack::Auth => {
-            (ver, op_code) = (1, 7);
-        }
-        DataPack::HeartBeat => {
-            (ver, op_code) = (1, 2);
-        }
-        _ => {}
-    }
-
-    let bs1 = head_len.to_be_bytes();
-    let bs2 = ver.to_be_bytes();
-    let bs3 = op_code.to_be_bytes();
-    let bs4 = sequence.to_be_bytes();
+// Decompress at most 'limit' bytes, longer output (e.g. a zip bomb) is an error.
+pub fn zlib_dec(data: &[u8], limit: usize) -> Result<Vec<u8>, std::io::Error> {
+    read_to_end_limited(ZlibDecoder::new(data), limit)
+}
 
-    for byte in bs1 {
-        data_pack[offset] = byte;
-        offset += 1;
-    }
-    for byte in bs2 {
-        data_pack[offset] = byte;
-        offset += 1;
-    }
-    for byte in bs3 {
-        data_pack[offset] = byte;
-        offset += 1;
-    }
-    for byte in bs4 {
-        data_pack[offset] = byte;
-        offset += 1;
-    }
+// Decompress at most 'limit' bytes, longer output (e.g. a zip bomb) is an error.
+pub fn brotli_dec(data: &[u8], limit: usize) -> Result<Vec<u8>, std::io::Error> {
+    read_to_end_limited(brotli::Decompressor::new(data, 4096), limit)
 }
 
-pub fn zlib_dec(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-    let mut d = ZlibDecoder::new(data);
+fn read_to_end_limited(reader: impl Read, limit: usize) -> Result<Vec<u8>, std::io::Error> {
     let mut buf = Vec::new();
-    d.read_to_end(&mut buf)?;
+    reader.take(limit as u64 + 1).read_to_end(&mut buf)?;
+    if buf.len() > limit {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("decompressed data is longer than {} bytes", limit),
+        ));
+    }
 
     Ok(buf)
 }
 
-pub fn split_packs(data: &[u8]) -> Vec<Vec<u8>> {
-    let total_len = data.len();
-    let mut packs: Vec<Vec<u8>> = vec![];
-    // start point of per pack
-    let mut start: usize = 0;
-    // length of per pack
-    let mut len = 0;
-    while start + len < total_len {
-        let mut pack: Vec<u8> = vec![];
-        len = u32::from_be_bytes(data[(start)..(start + 4)].try_into().unwrap()) as usize;
-
-        pack.extend_from_slice(&data[start + 16..start + len]);
-        packs.push(pack);
-
-        start += len;
-    }
+// Delay before the next retry: 1s, 2s, 4s, ... up to 64s, plus up to 50% of random jitter.
+pub fn backoff_delay(retries: u32) -> std::time::Duration {
+    let base_ms = 1000u64 << retries.min(6);
+    let jitter_ms = chrono::Utc::now().timestamp_subsec_nanos() as u64 % (base_ms / 2 + 1);
 
-    packs
+    std::time::Duration::from_millis(base_ms + jitter_ms)
 }
 
 pub fn timestamp_to_datetime_utc8(ts: u64) -> chrono::DateTime<chrono::Utc> {
@@ -88,11 +46,15 @@ pub fn timestamp_to_time_minutes(ts: u64) -> chrono::Duration {
     chrono::Duration::minutes(ts as i64 / 60)
 }
 
-pub fn duration(start_ts: u64) -> chrono::Duration {
+// If the live has not started ('start_ts' is 0 or in the future), return None.
+pub fn duration(start_ts: u64) -> Option<chrono::Duration> {
     let now_ts = chrono::Utc::now().timestamp() as u64;
-    let duration_secs = now_ts - start_ts;
+    if start_ts == 0 {
+        return None;
+    }
+    let duration_secs = now_ts.checked_sub(start_ts)?;
 
-    timestamp_to_time_minutes(duration_secs)
+    Some(timestamp_to_time_minutes(duration_secs))
 }
 
 pub fn display_duration(duration: chrono::Duration) -> String {
@@ -106,12 +68,12 @@ pub fn display_duration(duration: chrono::Duration) -> String {
     }
 }
 
-pub fn parse_description(description: &String, style: tui::style::Style) -> Vec<tui::text::Spans> {
+pub fn parse_description(description: &str, style: tui::style::Style) -> Vec<tui::text::Spans<'_>> {
     let lines = description
         .split("\\n")
         .collect::<Vec<_>>()
         .into_iter()
-        .filter(|item| item.len() > 0)
+        .filter(|item| !item.is_empty())
         .collect::<Vec<_>>();
     let mut spans_vec = vec![];
     if lines.len() == 1 {
@@ -133,15 +95,33 @@ pub fn parse_description(description: &String, style: tui::style::Style) -> Vec<
 }
 
 #[test]
-fn test_fill_datapack_header() {
-    let mut data_pack: Vec<u8> = vec![0; 32];
-    let expected: [u16; 16] = [0, 32, 16, 1, 0, 7, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0];
-    fill_datapack_header(DataPack::Auth, data_pack.as_mut_slice(), 1);
-    unsafe {
-        let raw_expected = &expected as *const u16 as *const u8;
-        let new_expected = std::slice::from_raw_parts(raw_expected.wrapping_sub(1), 32);
-        assert_eq!(new_expected, data_pack.as_slice());
+fn test_backoff_delay() {
+    for (retries, base_ms) in [(0, 1000), (1, 2000), (3, 8000), (6, 64000), (20, 64000)] {
+        let delay = backoff_delay(retries).as_millis() as u64;
+        assert!(delay >= base_ms && delay <= base_ms * 3 / 2);
+    }
+}
+
+#[test]
+fn test_duration() {
+    let now_ts = chrono::Utc::now().timestamp() as u64;
+
+    assert_eq!(duration(0), None);
+    assert_eq!(duration(now_ts + 3600), None);
+    assert_eq!(duration(now_ts - 3600), Some(chrono::Duration::minutes(60)));
+}
+
+#[test]
+fn test_brotli_dec() {
+    let expected = br#"{"cmd":"DANMU_MSG"}"#;
+    let mut enc_data = vec![];
+    {
+        let mut w = brotli::CompressorWriter::new(&mut enc_data, 4096, 11, 22);
+        w.write_all(expected).unwrap();
     }
+
+    assert_eq!(brotli_dec(&enc_data, expected.len()).unwrap(), expected);
+    assert!(brotli_dec(&enc_data, expected.len() - 1).is_err());
 }
 
 #[test]
@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use bili_live_chat::client::{DanmakuClient, EventStream, MultiRoomClient};
+use bili_live_chat::mock::{
+    Batch, Compression, MockOptions, MockServer, MOCK_POPULARITY, MOCK_TOKEN,
+};
+use bili_live_chat::{Error, LiveEvent, RoomEvent};
+use futures::StreamExt;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Receive the next event other than the popularity.
+async fn next_event(client: &mut DanmakuClient, rx: &mut mpsc::Receiver<LiveEvent>) -> LiveEvent {
+    let wait = async {
+        loop {
+            if let Ok(event) = rx.try_recv() {
+                if !matches!(event, LiveEvent::Popularity(_)) {
+                    return event;
+                }
+                continue;
+            }
+            client.receive().await.unwrap();
+        }
+    };
+
+    tokio::time::timeout(TIMEOUT, wait).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_connect_and_receive_scripted_batches() {
+    let script = vec![
+        Batch::new(
+            Compression::Zlib,
+            vec![
+                json!({ "cmd": "LIVE", "roomid": 3044248, "live_time": 1674000000 }),
+                json!({ "cmd": "WATCHED_CHANGE", "data": { "num": 42, "text_small": "42" } }),
+            ],
+        ),
+        Batch::new(
+            Compression::Brotli,
+            vec![json!({ "cmd": "ROOM_CHANGE", "data": { "title": "new title" } })],
+        ),
+        Batch::new(
+            Compression::None,
+            vec![json!({ "cmd": "SOMETHING_NEW", "data": {} })],
+        ),
+    ];
+    let server = MockServer::start(script).await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(3044248, &server.config(), tx);
+
+    tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let auths = server.record().auths;
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0]["key"], MOCK_TOKEN);
+    assert_eq!(auths[0]["roomid"], 3044248);
+    assert_eq!(auths[0]["protover"], 3);
+
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::Live(live) => assert_eq!(live.live_time, 1674000000),
+        event => panic!("unexpected event: {:?}", event),
+    }
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::WatchedChange(watched) => assert_eq!(watched.num, 42),
+        event => panic!("unexpected event: {:?}", event),
+    }
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::RoomChange(room_change) => assert_eq!(room_change.title, "new title"),
+        event => panic!("unexpected event: {:?}", event),
+    }
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::Unknown(raw) => assert_eq!(raw.cmd, "SOMETHING_NEW"),
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    // batches pushed after connecting
+    server.push(Batch::new(
+        Compression::Zlib,
+        vec![json!({ "cmd": "PREPARING", "roomid": 3044248 })],
+    ));
+    assert!(matches!(
+        next_event(&mut client, &mut rx).await,
+        LiveEvent::Preparing(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_heart_beat() {
+    let server = MockServer::start(vec![]).await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(1, &server.config(), tx);
+    let conn_write = tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap()
+        .unwrap();
+
+    DanmakuClient::send_heart_beat_to(&conn_write)
+        .await
+        .unwrap();
+    let wait = async {
+        loop {
+            client.receive().await.unwrap();
+            if let Ok(LiveEvent::Popularity(popularity)) = rx.try_recv() {
+                return popularity;
+            }
+        }
+    };
+    let popularity = tokio::time::timeout(TIMEOUT, wait).await.unwrap();
+
+    assert_eq!(popularity, MOCK_POPULARITY);
+    assert_eq!(server.record().heart_beats, 1);
+}
+
+#[tokio::test]
+async fn test_reconnect() {
+    let script = vec![Batch::new(
+        Compression::Brotli,
+        vec![json!({ "cmd": "WATCHED_CHANGE", "data": { "num": 1 } })],
+    )];
+    let server = MockServer::start(script).await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(1, &server.config(), tx);
+    tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        next_event(&mut client, &mut rx).await,
+        LiveEvent::WatchedChange(_)
+    ));
+
+    // the client authenticates again, then the script is pushed again
+    server.close_all();
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::Reconnecting(reconnecting) => assert_eq!(reconnecting.retries, 0),
+        event => panic!("unexpected event: {:?}", event),
+    }
+    let event = next_event(&mut client, &mut rx).await;
+    assert_eq!(event, LiveEvent::Reconnected);
+    assert!(matches!(
+        next_event(&mut client, &mut rx).await,
+        LiveEvent::WatchedChange(_)
+    ));
+    assert_eq!(server.record().auths.len(), 2);
+}
+
+#[tokio::test]
+async fn test_auth_rejected() {
+    let options = MockOptions {
+        token: Some("stale-token".to_owned()),
+        ..Default::default()
+    };
+    let server = MockServer::start_with(vec![], options).await.unwrap();
+    let (tx, _rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(1, &server.config(), tx);
+
+    let res = tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap();
+    assert!(matches!(res, Err(Error::AuthRejected(-101))));
+    // other hosts are not tried with the rejected token
+    let auths = server.record().auths;
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0]["key"], "stale-token");
+}
+
+#[tokio::test]
+async fn test_auth_timeout() {
+    let options = MockOptions {
+        silent_auth: true,
+        ..Default::default()
+    };
+    let server = MockServer::start_with(vec![], options).await.unwrap();
+    let (tx, _rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(1, &server.config(), tx);
+
+    let res = tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap();
+    assert!(matches!(res, Err(Error::AuthTimeout)));
+    assert_eq!(server.record().auths.len(), 1);
+}
+
+#[tokio::test]
+async fn test_fall_back_to_next_host() {
+    let script = vec![Batch::new(
+        Compression::Zlib,
+        vec![json!({ "cmd": "WATCHED_CHANGE", "data": { "num": 3 } })],
+    )];
+    let options = MockOptions {
+        dead_hosts: 2,
+        ..Default::default()
+    };
+    let server = MockServer::start_with(script, options).await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut client = DanmakuClient::new(1, &server.config(), tx);
+    tokio::time::timeout(TIMEOUT, client.connect())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(server.record().auths.len(), 1);
+    match next_event(&mut client, &mut rx).await {
+        LiveEvent::WatchedChange(watched) => assert_eq!(watched.num, 3),
+        event => panic!("unexpected event: {:?}", event),
+    }
+}
+
+#[tokio::test]
+async fn test_event_stream() {
+    let script = vec![Batch::new(
+        Compression::Zlib,
+        vec![json!({ "cmd": "WATCHED_CHANGE", "data": { "num": 7 } })],
+    )];
+    let server = MockServer::start(script).await.unwrap();
+    let (mut stream, sender) =
+        tokio::time::timeout(TIMEOUT, EventStream::connect(1, &server.config()))
+            .await
+            .unwrap()
+            .unwrap();
+
+    match tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap() {
+        Some(Ok(LiveEvent::WatchedChange(watched))) => assert_eq!(watched.num, 7),
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    sender.heart_beat().await.unwrap();
+    match tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap() {
+        Some(Ok(LiveEvent::Popularity(popularity))) => assert_eq!(popularity, MOCK_POPULARITY),
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    // the stream ends instead of reconnecting
+    sender.close().await.unwrap();
+    assert!(tokio::time::timeout(TIMEOUT, stream.next())
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(server.record().auths.len(), 1);
+}
+
+#[tokio::test]
+async fn test_multi_room_client() {
+    let server = MockServer::start(vec![]).await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut client = MultiRoomClient::new(&server.config(), tx);
+    let results = tokio::time::timeout(TIMEOUT, client.subscribe_all(&[1, 2]))
+        .await
+        .unwrap();
+    assert!(results.iter().all(|res| res.is_ok()));
+    assert_eq!(server.record().auths.len(), 2);
+
+    // the number watched is the room the batch is pushed to
+    for room_id in [1, 2] {
+        let watched = json!({ "cmd": "WATCHED_CHANGE", "data": { "num": room_id } });
+        server.push_to(room_id, Batch::new(Compression::Zlib, vec![watched]));
+    }
+    let mut tagged = vec![];
+    while tagged.len() < 2 {
+        match tokio::time::timeout(TIMEOUT, rx.recv()).await.unwrap() {
+            Some(RoomEvent {
+                room_id,
+                event: LiveEvent::WatchedChange(watched),
+            }) => tagged.push((room_id, watched.num)),
+            Some(_) => {}
+            None => panic!("channel closed"),
+        }
+    }
+    tagged.sort();
+    assert_eq!(tagged, vec![(1, 1), (2, 2)]);
+
+    assert!(client.unsubscribe(1));
+    assert!(!client.unsubscribe(1));
+    assert_eq!(client.room_ids(), vec![2]);
+}